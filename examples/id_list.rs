@@ -9,11 +9,15 @@ fn main() {
 
     let root = detector_definition.component_tree;
 
-    let points = root.get_special_type_points();
+    let detectors = root.get_detectors().expect("could not assign the ids");
 
     let ids = detector_definition.id_lists.get("ids").unwrap().get_ids();
 
     dbg!(&ids.len());
 
-    assert_eq!(points.len(), ids.len());
+    assert_eq!(detectors.len(), ids.len());
+
+    for (detector, id) in detectors.iter().zip(ids) {
+        assert_eq!(detector.id, Some(id));
+    }
 }
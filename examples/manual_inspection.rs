@@ -9,6 +9,8 @@ fn main() {
 
     let types = detector_definition.types;
 
+    let defaults = detector_definition.defaults;
+
     let root = types.get("VoxelsRoot").unwrap();
 
     dbg!(root);
@@ -46,7 +48,7 @@ fn main() {
 
     for element in 0..n_elements {
         let rotation_angle =
-            start_rot + (end_rot - start_rot) * (element as f32 / n_elements as f32);
+            start_rot + (end_rot - start_rot) * (element as f32 / (n_elements - 1) as f32);
 
         let rotation = nalgebra::Rotation3::from_axis_angle(
             &nalgebra::Unit::new_normalize(nalgebra::Vector3::new(
@@ -54,7 +56,7 @@ fn main() {
                 rotation_axis.y,
                 rotation_axis.z,
             )),
-            rotation_angle,
+            defaults.angle_to_radians(rotation_angle),
         );
 
         for component in x00b_type.components.iter() {
@@ -65,8 +67,7 @@ fn main() {
                 .translation
                 .first()
                 .unwrap()
-                .clone()
-                .into_cartesian();
+                .to_cartesian(&defaults);
 
             dbg!(center_point);
 
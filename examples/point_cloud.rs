@@ -7,7 +7,7 @@ async fn main() {
 
 struct DetectorPlotInformation {
     points: Vec<[f32; 3]>,
-    units: Vec<usize>,
+    units: Vec<i64>,
 }
 
 fn get_detector_information() -> DetectorPlotInformation {
@@ -33,7 +33,9 @@ fn get_detector_information() -> DetectorPlotInformation {
 
     let root = detector_definition.component_tree;
 
-    let points = root.get_special_type_points();
+    let points = root
+        .get_special_type_points()
+        .expect("could not expand the detectors");
 
     let points = points.into_iter().map(|p| [p.x, p.y, p.z]).collect();
 
@@ -66,7 +68,7 @@ pub async fn run() {
 
     let (min_units, max_units) = units
         .iter()
-        .fold((usize::MAX, usize::MIN), |(min, max), &unit| {
+        .fold((i64::MAX, i64::MIN), |(min, max), &unit| {
             (min.min(unit), max.max(unit))
         });
 
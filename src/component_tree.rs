@@ -4,12 +4,19 @@
 use std::{collections::BTreeMap, fmt::Debug, sync::Arc};
 
 use anyhow::Context;
-use nalgebra::{Rotation3, Translation3};
+use nalgebra::{Isometry3, Point2, Rotation3, Translation3, UnitQuaternion};
 
 use crate::{
-    structs::{Component, Type},
+    defaults::{Defaults, SphericalOffsets},
+    detectors::{GridDetector, RectangularDetector, StructuredDetector},
+    idlists::{IDList, IDListMismatch},
+    parameter_file::ComponentLink,
+    parameters::{FromParameter, Parameter, ParameterScope, ParameterType, Parameters},
+    sample_logs::SampleLogs,
+    shapes::{BoundingBox, Shapes},
+    structs::{apply_facing, Component, Location, Rotation, Translation, Type},
     types::{SpecialTypes, Types},
-    utils::Axes,
+    utils::{cartesian_to_spherical, spherical_to_cartesian, Axes},
     Point,
 };
 
@@ -20,21 +27,36 @@ pub struct ComponentTree(ComponentTreeNode);
 impl ComponentTree {
     pub(crate) fn from_types_and_components(
         types: Arc<Types>,
-        components: BTreeMap<String, Component>,
+        defaults: Arc<Defaults>,
+        id_lists: Arc<BTreeMap<String, IDList>>,
+        components: Vec<Component>,
     ) -> Self {
-        let mut root = ComponentTreeNode::root(types.clone());
-
-        for (_name, component) in components.iter() {
-            let tree = Self::get_node_from_component(types.clone(), component);
+        let mut root = ComponentTreeNode::root(types.clone(), defaults.clone(), id_lists.clone());
+
+        for component in components.iter() {
+            let tree = Self::get_node_from_component(
+                types.clone(),
+                defaults.clone(),
+                id_lists.clone(),
+                component,
+            );
 
             root.children.push(Box::new(tree));
         }
 
+        root.update_parameter_scopes();
+
         ComponentTree(root)
     }
 
-    fn get_node_from_component(types: Arc<Types>, component: &Component) -> ComponentTreeNode {
-        let mut node = ComponentTreeNode::new(component, types.clone());
+    fn get_node_from_component(
+        types: Arc<Types>,
+        defaults: Arc<Defaults>,
+        id_lists: Arc<BTreeMap<String, IDList>>,
+        component: &Component,
+    ) -> ComponentTreeNode {
+        let mut node =
+            ComponentTreeNode::new(component, types.clone(), defaults.clone(), id_lists.clone());
 
         let type_ = types
             .get(&component.type_name)
@@ -44,7 +66,17 @@ impl ComponentTree {
         let mut children = Vec::new();
 
         for component in type_.components.iter() {
-            let child = Self::get_node_from_component(types.clone(), component);
+            // Components that are left out at every location of the parent are not part of the tree
+            if component.is_excluded_by(&node.component) {
+                continue;
+            }
+
+            let child = Self::get_node_from_component(
+                types.clone(),
+                defaults.clone(),
+                id_lists.clone(),
+                component,
+            );
 
             children.push(Box::new(child));
         }
@@ -53,6 +85,65 @@ impl ComponentTree {
 
         node
     }
+
+    /// Attaches the parameters of the `ComponentLink` to all components that match its name or path. \
+    /// A link with the name of the instrument targets the root of the tree. \
+    /// If the last part of the name only matches some of the locations of a component, the parameters are attached
+    /// to those locations (see `Component::location_parameters`), otherwise to the component itself. \
+    /// Parameters that already exist on a component are overwritten. \
+    /// Returns `false` if no component matches the link.
+    pub fn apply_component_link(
+        &mut self,
+        component_link: &ComponentLink,
+        instrument_name: &str,
+    ) -> bool {
+        let paths = self
+            .0
+            .find_paths_by_name(&component_link.name, instrument_name);
+
+        let name = component_link
+            .name
+            .rsplit('/')
+            .find(|segment| !segment.is_empty())
+            .unwrap_or_default();
+
+        for path in paths.iter() {
+            let node = self
+                .0
+                .get_node_mut(path)
+                .expect("path was found in the tree");
+
+            let location_indices = node.component.location_indices_by_name(name);
+
+            if path.is_empty() || location_indices.len() == node.component.location_names().len() {
+                node.component
+                    .parameters
+                    .extend(component_link.parameters.clone());
+
+                node.update_parameter_scopes();
+
+                continue;
+            }
+
+            for index in location_indices {
+                node.component
+                    .location_parameters
+                    .entry(index)
+                    .or_default()
+                    .extend(component_link.parameters.clone());
+            }
+        }
+
+        !paths.is_empty()
+    }
+
+    /// Moves and rotates the components according to their position parameters
+    /// (`x`, `y`, `z`, `r-position`, `t-position`, `p-position`, `rotx`, `roty`, `rotz`). \
+    /// Parameters defined with `logfile` are resolved with the sample logs, fixed values are applied as well. \
+    /// See `ComponentTreeNode::apply_position_parameters` for the details.
+    pub fn apply_sample_logs(&mut self, sample_logs: &SampleLogs) -> anyhow::Result<()> {
+        self.0.apply_position_parameters(sample_logs)
+    }
 }
 
 impl std::ops::Deref for ComponentTree {
@@ -63,6 +154,94 @@ impl std::ops::Deref for ComponentTree {
     }
 }
 
+/// Which locations are used when the tree is expanded into the positions of the components.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Geometry {
+    /// The physical locations, e.g. for the 3D view.
+    #[default]
+    Physical,
+    /// The `neutronic` locations where they are given (the physical locations otherwise),
+    /// e.g. for unit conversions with the effective flight paths of indirect geometry instruments.
+    Neutronic,
+}
+
+/// A detector (or monitor) of the expanded component tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpandedDetector {
+    /// The id of the detector, `None` if no `idlist` applies to it.
+    pub id: Option<i64>,
+    /// The transformation from the local coordinates of the detector to the coordinates of the instrument.
+    pub transformation: Isometry3<f32>,
+    /// The special type of the component the detector was expanded from.
+    pub special_type: SpecialTypes,
+    /// The name of the type of the detector. For the pixels of rectangular, structured and grid detectors
+    /// this is the pixel type.
+    pub type_name: String,
+    /// The corners of the pixel in the coordinates of the instrument. Only known for structured detectors.
+    pub corners: Option<[Point; 4]>,
+}
+
+impl ExpandedDetector {
+    /// The position of the detector in the coordinates of the instrument.
+    pub fn position(&self) -> Point {
+        self.transformation * Point::origin()
+    }
+
+    /// The shape of the type of the detector in the coordinates of the instrument. \
+    /// Returns `None` if the type has no shape. \
+    /// The explicit `bounding-box` of the type is moved with the shape by `ExpandedDetector::bounding_box`.
+    pub fn shape(&self, types: &Types) -> anyhow::Result<Option<Shapes>> {
+        self.get_type(types)?
+            .shape
+            .as_ref()
+            .map(|shape| shape.transformed(&self.transformation))
+            .transpose()
+    }
+
+    /// The `bounding-box` of the type of the detector in the coordinates of the instrument,
+    /// see `BoundingBox::transformed`. \
+    /// Returns `None` if the type does not define a bounding box.
+    /// # Example
+    /// ```
+    /// use mantid_idf::DetectorDefinition;
+    ///
+    /// let content = r#"
+    ///     <instrument name="Test">
+    ///         <component type="pixel"><location x="1"/></component>
+    ///         <type name="pixel" is="detector">
+    ///             <sphere id="shape"><centre x="0" y="0" z="0"/><radius val="0.5"/></sphere>
+    ///             <bounding-box>
+    ///                 <x-min val="-0.5"/><x-max val="0.5"/>
+    ///                 <y-min val="-0.5"/><y-max val="0.5"/>
+    ///                 <z-min val="-0.5"/><z-max val="0.5"/>
+    ///             </bounding-box>
+    ///         </type>
+    ///     </instrument>
+    /// "#;
+    ///
+    /// let detector_definition = DetectorDefinition::from_str(content).unwrap();
+    ///
+    /// let detectors = detector_definition.component_tree.get_detectors().unwrap();
+    ///
+    /// let bounding_box = detectors[0].bounding_box(&detector_definition.types).unwrap().unwrap();
+    ///
+    /// assert_eq!((bounding_box.x_min, bounding_box.x_max), (0.5, 1.5));
+    /// ```
+    pub fn bounding_box(&self, types: &Types) -> anyhow::Result<Option<BoundingBox>> {
+        Ok(self
+            .get_type(types)?
+            .bounding_box
+            .as_ref()
+            .map(|bounding_box| bounding_box.transformed(&self.transformation)))
+    }
+
+    fn get_type<'a>(&self, types: &'a Types) -> anyhow::Result<&'a Type> {
+        types
+            .get(&self.type_name)
+            .context(format!("could not find type {}", self.type_name))
+    }
+}
+
 /// A node in a tree of `Component`s \
 /// The nodes contains the `Component` and its children
 #[derive(Clone, Default)]
@@ -72,6 +251,10 @@ pub struct ComponentTreeNode {
     /// The `Component` of the node
     pub component: Component,
     types: Arc<Types>,
+    defaults: Arc<Defaults>,
+    id_lists: Arc<BTreeMap<String, IDList>>,
+    /// The parameters of all parents of the node, used for the hierarchical parameter lookup.
+    parent_parameters: Option<Arc<ParameterScope>>,
 }
 
 impl Debug for ComponentTreeNode {
@@ -84,19 +267,328 @@ impl Debug for ComponentTreeNode {
 }
 
 impl ComponentTreeNode {
-    fn new(component: &Component, types: Arc<Types>) -> Self {
+    fn new(
+        component: &Component,
+        types: Arc<Types>,
+        defaults: Arc<Defaults>,
+        id_lists: Arc<BTreeMap<String, IDList>>,
+    ) -> Self {
         Self {
             component: component.clone(),
             children: Vec::new(),
             types,
+            defaults,
+            id_lists,
+            parent_parameters: None,
         }
     }
 
-    fn root(types: Arc<Types>) -> Self {
+    fn root(
+        types: Arc<Types>,
+        defaults: Arc<Defaults>,
+        id_lists: Arc<BTreeMap<String, IDList>>,
+    ) -> Self {
         Self {
             component: Component::default(),
             children: Vec::new(),
             types,
+            defaults,
+            id_lists,
+            parent_parameters: None,
+        }
+    }
+
+    /// Returns the `Defaults` of the instrument the node belongs to.
+    pub fn get_defaults(&self) -> &Defaults {
+        &self.defaults
+    }
+
+    /// Searches for a parameter with the given name. \
+    /// The parameters of the component take precedence over the parameters of its `Type`. \
+    /// If `recursive` is `true` and the parameter is not found, the parents of the node are searched
+    /// (starting with the direct parent), like the `ParameterMap` of Mantid does.
+    pub fn find_parameter(&self, name: &str, recursive: bool) -> Option<&Parameter> {
+        if let Some(parameter) = self.component.parameters.get(name) {
+            return Some(parameter);
+        }
+
+        if let Some(parameter) = self.get_type_name().parameters.get(name) {
+            return Some(parameter);
+        }
+
+        match recursive {
+            true => self.parent_parameters.as_ref()?.find(name),
+            false => None,
+        }
+    }
+
+    /// Searches for a parameter with the given name that applies to the location with the given index
+    /// (see `Component::location_parameters`). \
+    /// The parameters of the location take precedence over the parameters found by `find_parameter`.
+    pub fn find_location_parameter(
+        &self,
+        name: &str,
+        location: usize,
+        recursive: bool,
+    ) -> Option<&Parameter> {
+        self.component
+            .location_parameters
+            .get(&location)
+            .and_then(|parameters| parameters.get(name))
+            .or_else(|| self.find_parameter(name, recursive))
+    }
+
+    /// Returns the value of the parameter converted to `T`. \
+    /// Searches the node, its `Type` and all of its parents. \
+    /// Returns `Ok(None)` if the parameter does not exist and an error if the value can not be converted.
+    /// # Example
+    /// ```
+    /// use mantid_idf::DetectorDefinition;
+    ///
+    /// let content = r#"
+    ///     <instrument name="Test">
+    ///         <component type="bank">
+    ///             <location/>
+    ///             <parameter name="TubePressure"><value val="10.0"/></parameter>
+    ///         </component>
+    ///         <type name="bank">
+    ///             <component type="pixel"><location/></component>
+    ///         </type>
+    ///         <type name="pixel" is="detector"/>
+    ///     </instrument>
+    /// "#;
+    ///
+    /// let detector_definition = DetectorDefinition::from_str(content).unwrap();
+    ///
+    /// let pixel = &detector_definition.component_tree.get_nodes_by_name("pixel")[0];
+    ///
+    /// assert_eq!(pixel.get_parameter::<f64>("TubePressure").unwrap(), Some(10.0));
+    /// assert_eq!(pixel.get_local_parameter::<f64>("TubePressure").unwrap(), None);
+    /// ```
+    pub fn get_parameter<T: FromParameter>(&self, name: &str) -> anyhow::Result<Option<T>> {
+        self.find_parameter(name, true)
+            .map(|parameter| parameter.get_value())
+            .transpose()
+    }
+
+    /// Returns the value of the parameter converted to `T`. \
+    /// Only searches the node itself and its `Type`, not its parents.
+    pub fn get_local_parameter<T: FromParameter>(&self, name: &str) -> anyhow::Result<Option<T>> {
+        self.find_parameter(name, false)
+            .map(|parameter| parameter.get_value())
+            .transpose()
+    }
+
+    /// Returns the value of the parameter converted to `T` for the location with the given index. \
+    /// Searches the parameters of the location first, then the node, its `Type` and all of its parents.
+    /// # Example
+    /// ```
+    /// use mantid_idf::{parameter_file::ParameterFile, DetectorDefinition};
+    ///
+    /// let content = r#"
+    ///     <instrument name="Test">
+    ///         <component type="tube">
+    ///             <location name="tube1"/>
+    ///             <location name="tube2" x="1"/>
+    ///         </component>
+    ///         <type name="tube" is="detector"/>
+    ///     </instrument>
+    /// "#;
+    ///
+    /// let mut detector_definition = DetectorDefinition::from_str(content).unwrap();
+    ///
+    /// let parameter_file = ParameterFile::from_str(r#"
+    ///     <parameter-file instrument="Test">
+    ///         <component-link name="tube2">
+    ///             <parameter name="DeadTime"><value val="0.5"/></parameter>
+    ///         </component-link>
+    ///     </parameter-file>
+    /// "#).unwrap();
+    ///
+    /// detector_definition.apply_parameter_file(&parameter_file);
+    ///
+    /// let tube = &detector_definition.component_tree.get_nodes_by_name("tube2")[0];
+    ///
+    /// assert_eq!(tube.get_location_parameter::<f64>("DeadTime", 0).unwrap(), None);
+    /// assert_eq!(tube.get_location_parameter::<f64>("DeadTime", 1).unwrap(), Some(0.5));
+    /// ```
+    pub fn get_location_parameter<T: FromParameter>(
+        &self,
+        name: &str,
+        location: usize,
+    ) -> anyhow::Result<Option<T>> {
+        self.find_location_parameter(name, location, true)
+            .map(|parameter| parameter.get_value())
+            .transpose()
+    }
+
+    /// Returns all parameters that apply to the node. \
+    /// The parameters of the component take precedence over the parameters of its `Type`. \
+    /// If `recursive` is `true` the parameters of the parents are included, closer parents take precedence.
+    pub fn get_parameters(&self, recursive: bool) -> Parameters {
+        let mut parameters = Parameters::new();
+
+        if let (true, Some(parent_parameters)) = (recursive, self.parent_parameters.as_ref()) {
+            parent_parameters.collect_into(&mut parameters);
+        }
+
+        parameters.extend(self.get_type_name().parameters.clone());
+        parameters.extend(self.component.parameters.clone());
+
+        parameters
+    }
+
+    /// Resolves the numeric values of all parameters that apply to the node (including the parents). \
+    /// `logfile` parameters are resolved with the sample logs. \
+    /// Parameters of type `bool` or `string`, `formula` parameters and parameters with a missing sample log are skipped.
+    /// # Example
+    /// ```
+    /// use mantid_idf::{sample_logs::{SampleLogs, TimeSeries}, DetectorDefinition};
+    ///
+    /// let content = r#"
+    ///     <instrument name="Test">
+    ///         <component type="bank">
+    ///             <location/>
+    ///             <parameter name="TubePressure"><logfile id="pressure" eq="value/10"/></parameter>
+    ///         </component>
+    ///         <type name="bank" is="detector"/>
+    ///     </instrument>
+    /// "#;
+    ///
+    /// let detector_definition = DetectorDefinition::from_str(content).unwrap();
+    ///
+    /// let bank = &detector_definition.component_tree.get_nodes_by_name("bank")[0];
+    ///
+    /// let sample_logs = SampleLogs::from([("pressure".to_string(), TimeSeries::from_values(vec![100.0]))]);
+    ///
+    /// assert_eq!(bank.resolve_parameters(&sample_logs).unwrap()["TubePressure"], 10.0);
+    /// ```
+    pub fn resolve_parameters(
+        &self,
+        sample_logs: &SampleLogs,
+    ) -> anyhow::Result<BTreeMap<String, f64>> {
+        let mut values = BTreeMap::new();
+
+        for (name, parameter) in self.get_parameters(true) {
+            if matches!(
+                parameter.parameter_type,
+                ParameterType::Bool | ParameterType::String
+            ) {
+                continue;
+            }
+
+            if let Some(value) = parameter.resolve(sample_logs)? {
+                values.insert(name, value);
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Resolves the position parameters of a location of the node, see `ComponentTreeNode::find_location_parameter`. \
+    /// The parameters of the parents are not included.
+    fn resolve_position_parameters(
+        &self,
+        location: usize,
+        sample_logs: &SampleLogs,
+    ) -> anyhow::Result<PositionParameters> {
+        let resolve = |names: [&str; 3]| -> anyhow::Result<[Option<f32>; 3]> {
+            let mut values = [None; 3];
+
+            for (value, name) in values.iter_mut().zip(names) {
+                if let Some(parameter) = self.find_location_parameter(name, location, false) {
+                    *value = parameter.resolve(sample_logs)?.map(|value| value as f32);
+                }
+            }
+
+            Ok(values)
+        };
+
+        Ok(PositionParameters {
+            cartesian: resolve(["x", "y", "z"])?,
+            spherical: resolve(["r-position", "t-position", "p-position"])?,
+            rotation: resolve(["rotx", "roty", "rotz"])?,
+        })
+    }
+
+    /// Applies the position parameters to all locations of the node and its descendants, like Mantid does
+    /// when it loads the sample logs of a run. \
+    /// `x`, `y` and `z` (metres) replace the coordinates of the position relative to the parent. \
+    /// `r-position` (metres), `t-position` and `p-position` (degrees) replace the spherical coordinates of that position. \
+    /// `rotx`, `roty` and `rotz` (degrees) replace the rotation relative to the parent with `Rx * Ry * Rz`,
+    /// missing angles are zero. \
+    /// Parameters of a single location (see `Component::location_parameters`) only move that location. \
+    /// The nested `rot` and `trans` tags of a moved or rotated location are folded into its attributes. \
+    /// If any location of a component is moved, the elements of its `locations` are converted to single `location`s,
+    /// which keeps the order and with that the index of every location.
+    fn apply_position_parameters(&mut self, sample_logs: &SampleLogs) -> anyhow::Result<()> {
+        if !self.component.is_root() {
+            let location_count = self.component.location.len()
+                + self
+                    .component
+                    .locations
+                    .iter()
+                    .map(|locations| locations.n_elements as usize)
+                    .sum::<usize>();
+
+            let position_parameters = (0..location_count)
+                .map(|location| self.resolve_position_parameters(location, sample_logs))
+                .collect::<anyhow::Result<Vec<_>>>()
+                .context(format!(
+                    "could not resolve position parameter of component {}",
+                    self.component.type_name
+                ))?;
+
+            let defaults = self.defaults.clone();
+
+            if position_parameters.iter().any(PositionParameters::is_set) {
+                let locations = std::mem::take(&mut self.component.locations);
+
+                self.component
+                    .location
+                    .extend(locations.iter().flat_map(|locations| {
+                        (0..locations.n_elements)
+                            .map(|element| locations.to_location(element, &defaults))
+                    }));
+            }
+
+            for (location, position_parameters) in self
+                .component
+                .location
+                .iter_mut()
+                .zip(position_parameters.iter())
+            {
+                position_parameters.apply(location, &defaults);
+            }
+        }
+
+        for child in self.children.iter_mut() {
+            child.apply_position_parameters(sample_logs)?;
+        }
+
+        Ok(())
+    }
+
+    /// Rebuilds the parameter scopes of all descendants of the node. \
+    /// Has to be called after the parameters of the node changed.
+    fn update_parameter_scopes(&mut self) {
+        if self.children.is_empty() {
+            return;
+        }
+
+        let mut parameters = self.get_type_name().parameters.clone();
+
+        parameters.extend(self.component.parameters.clone());
+
+        let scope = Arc::new(ParameterScope {
+            parameters,
+            parent: self.parent_parameters.clone(),
+        });
+
+        for child in self.children.iter_mut() {
+            child.parent_parameters = Some(scope.clone());
+
+            child.update_parameter_scopes();
         }
     }
 
@@ -147,13 +639,129 @@ impl ComponentTreeNode {
     /// assert!(child.is_none());
     /// ```
     pub fn get_child_by_name(&self, name: &str) -> Option<&ComponentTreeNode> {
-        for child in self.children.iter() {
-            if child.component.type_name == name {
-                return Some(child);
+        self.children
+            .iter()
+            .find(|child| child.component.type_name == name)
+            .map(|child| child.as_ref())
+    }
+
+    /// Returns all nodes below this node that match the name. \
+    /// The name can also be a path of names separated by `/`, e.g. `bank1/tube3`. \
+    /// Each part of the path is searched for in the descendants of the previous match.
+    /// # Example
+    /// ```
+    /// use mantid_idf::component_tree::ComponentTreeNode;
+    ///
+    /// let node = ComponentTreeNode::default();
+    ///
+    /// let nodes = node.get_nodes_by_name("VoxelsRoot/Voxels");
+    ///
+    /// assert!(nodes.is_empty());
+    /// ```
+    pub fn get_nodes_by_name(&self, name: &str) -> Vec<&ComponentTreeNode> {
+        self.find_paths_by_name(name, "")
+            .iter()
+            .filter_map(|path| self.get_node(path))
+            .collect()
+    }
+
+    /// Returns the paths (indices of the children) of all nodes that match the name or path.
+    fn find_paths_by_name(&self, name: &str, instrument_name: &str) -> Vec<Vec<usize>> {
+        let mut segments = name.split('/').filter(|segment| !segment.is_empty());
+
+        let Some(first_segment) = segments.next() else {
+            return Vec::new();
+        };
+
+        let mut paths = match self.component.is_root() && first_segment == instrument_name {
+            true => vec![Vec::new()],
+            false => self.find_descendant_paths_by_name(first_segment),
+        };
+
+        for segment in segments {
+            paths = paths
+                .into_iter()
+                .flat_map(|path| {
+                    let node = self.get_node(&path).expect("path was found in the tree");
+
+                    node.find_descendant_paths_by_name(segment)
+                        .into_iter()
+                        .map(move |sub_path| [path.clone(), sub_path].concat())
+                })
+                .collect();
+        }
+
+        paths.sort();
+        paths.dedup();
+
+        paths
+    }
+
+    fn find_descendant_paths_by_name(&self, name: &str) -> Vec<Vec<usize>> {
+        let mut paths = Vec::new();
+
+        for (index, child) in self.children.iter().enumerate() {
+            if child.component.has_name(name) {
+                paths.push(vec![index]);
+            }
+
+            for sub_path in child.find_descendant_paths_by_name(name) {
+                paths.push([vec![index], sub_path].concat());
             }
         }
 
-        None
+        paths
+    }
+
+    fn get_node(&self, path: &[usize]) -> Option<&ComponentTreeNode> {
+        match path.split_first() {
+            Some((index, rest)) => self.children.get(*index)?.get_node(rest),
+            None => Some(self),
+        }
+    }
+
+    fn get_node_mut(&mut self, path: &[usize]) -> Option<&mut ComponentTreeNode> {
+        match path.split_first() {
+            Some((index, rest)) => self.children.get_mut(*index)?.get_node_mut(rest),
+            None => Some(self),
+        }
+    }
+
+    /// Returns the node and all nodes below it that have a `side-by-side-view-location`, together with that location. \
+    /// Mantid uses these locations to arrange the banks in the side-by-side view of the instrument view.
+    /// # Example
+    /// ```
+    /// use mantid_idf::DetectorDefinition;
+    ///
+    /// let content = r#"
+    ///     <instrument name="Test">
+    ///         <component type="bank">
+    ///             <location/>
+    ///             <side-by-side-view-location x="0.5" y="-1.0"/>
+    ///         </component>
+    ///         <type name="bank" is="detector"/>
+    ///     </instrument>
+    /// "#;
+    ///
+    /// let detector_definition = DetectorDefinition::from_str(content).unwrap();
+    ///
+    /// let locations = detector_definition.component_tree.get_side_by_side_view_locations();
+    ///
+    /// assert_eq!(locations[0].0.component.type_name, "bank");
+    /// assert_eq!(locations[0].1, nalgebra::Point2::new(0.5, -1.0));
+    /// ```
+    pub fn get_side_by_side_view_locations(&self) -> Vec<(&ComponentTreeNode, Point2<f32>)> {
+        let mut locations = Vec::new();
+
+        if let Some(location) = self.component.side_by_side_view_location {
+            locations.push((self, location));
+        }
+
+        for child in self.children.iter() {
+            locations.extend(child.get_side_by_side_view_locations());
+        }
+
+        locations
     }
 
     /// Returns the `SpecialTypes` of the node
@@ -207,291 +815,583 @@ impl ComponentTreeNode {
     }
 
     /// Returns the points of any special type, transformed by the node and all previous parents \
+    /// The ids are not assigned, see `get_detectors` for the detectors together with their ids. \
     /// TODO: Add example + more explanation
-    pub fn get_special_type_points(&self) -> Vec<Point> {
-        let translations = vec![];
-        let rotations = vec![];
+    pub fn get_special_type_points(&self) -> anyhow::Result<Vec<Point>> {
+        self.get_special_type_points_in(Geometry::Physical)
+    }
+
+    /// Like `get_special_type_points`, but the positions are taken from the given `Geometry`.
+    /// # Example
+    /// ```
+    /// use mantid_idf::{component_tree::Geometry, DetectorDefinition, Point};
+    ///
+    /// let content = r#"
+    ///     <instrument name="Test">
+    ///         <component type="analyser-pixel">
+    ///             <location z="1"><neutronic z="5"/></location>
+    ///         </component>
+    ///         <type name="analyser-pixel" is="detector"/>
+    ///     </instrument>
+    /// "#;
+    ///
+    /// let detector_definition = DetectorDefinition::from_str(content).unwrap();
+    ///
+    /// let tree = &detector_definition.component_tree;
+    ///
+    /// assert_eq!(tree.get_special_type_points_in(Geometry::Physical).unwrap(), [Point::new(0.0, 0.0, 1.0)]);
+    /// assert_eq!(tree.get_special_type_points_in(Geometry::Neutronic).unwrap(), [Point::new(0.0, 0.0, 5.0)]);
+    /// ```
+    pub fn get_special_type_points_in(&self, geometry: Geometry) -> anyhow::Result<Vec<Point>> {
+        Ok(self
+            .get_special_type_transformations_in(geometry)?
+            .iter()
+            .map(|transformation| transformation * Point::origin())
+            .collect())
+    }
 
-        let (points, _ids) = self.recursive_transform_points(translations, rotations);
+    /// Returns the transformations of all components with a special type (e.g. the pixels of the detectors)
+    /// from their local coordinates to the coordinates of the instrument. \
+    /// The transformations include the `facing` of the components, so they can be used to orient the shapes
+    /// (see `Shapes::transformed`) and to compute normal vectors.
+    /// # Example
+    /// ```
+    /// use mantid_idf::{DetectorDefinition, Point};
+    ///
+    /// let content = r#"
+    ///     <instrument name="Test">
+    ///         <defaults><components-are-facing x="0" y="0" z="0"/></defaults>
+    ///         <component type="pixel"><location x="2"/></component>
+    ///         <type name="pixel" is="detector"/>
+    ///     </instrument>
+    /// "#;
+    ///
+    /// let detector_definition = DetectorDefinition::from_str(content).unwrap();
+    ///
+    /// let transformations = detector_definition.component_tree.get_special_type_transformations().unwrap();
+    ///
+    /// // The z-axis of the pixel points away from the sample
+    /// let normal = transformations[0] * nalgebra::Vector3::z();
+    ///
+    /// assert!((normal - nalgebra::Vector3::x()).norm() < 1e-6);
+    /// ```
+    pub fn get_special_type_transformations(&self) -> anyhow::Result<Vec<Isometry3<f32>>> {
+        self.get_special_type_transformations_in(Geometry::Physical)
+    }
 
-        points
+    /// Like `get_special_type_transformations`, but the locations are taken from the given `Geometry`.
+    pub fn get_special_type_transformations_in(
+        &self,
+        geometry: Geometry,
+    ) -> anyhow::Result<Vec<Isometry3<f32>>> {
+        Ok(self
+            .expand(
+                &Placement::from(Isometry3::identity()),
+                geometry,
+                &mut Vec::new(),
+            )?
+            .into_iter()
+            .map(|detector| detector.transformation)
+            .collect())
     }
 
-    /// Recursively transforms the points of the node and its children \
+    /// Returns all detectors (and monitors) below the node together with their ids. \
+    /// The ids come from the `idlist` of the closest component above the detector that has the `idlist` attribute,
+    /// in the order the detectors are expanded. Rectangular detectors number their pixels themselves. \
+    /// Errors if the number of ids of an `idlist` does not match the number of detectors it is applied to.
+    /// # Example
+    /// ```
+    /// use mantid_idf::DetectorDefinition;
+    ///
+    /// let content = r#"
+    ///     <instrument name="Test">
+    ///         <component type="tube" idlist="tube-ids"><location/></component>
+    ///         <type name="tube">
+    ///             <component type="pixel">
+    ///                 <locations y="0" y-end="0.1" n-elements="3"/>
+    ///             </component>
+    ///         </type>
+    ///         <type name="pixel" is="detector"/>
+    ///         <idlist idname="tube-ids"><id start="10" end="12"/></idlist>
+    ///     </instrument>
+    /// "#;
+    ///
+    /// let detector_definition = DetectorDefinition::from_str(content).unwrap();
+    ///
+    /// let detectors = detector_definition.component_tree.get_detectors().unwrap();
+    ///
+    /// assert_eq!(detectors.iter().map(|detector| detector.id).collect::<Vec<_>>(), [Some(10), Some(11), Some(12)]);
+    /// ```
+    pub fn get_detectors(&self) -> anyhow::Result<Vec<ExpandedDetector>> {
+        self.get_detectors_in(Geometry::Physical)
+    }
+
+    /// Like `get_detectors`, but the locations are taken from the given `Geometry`.
+    pub fn get_detectors_in(&self, geometry: Geometry) -> anyhow::Result<Vec<ExpandedDetector>> {
+        self.recursive_detectors(Isometry3::identity(), geometry)
+    }
+
+    /// Recursively expands the node and its children into the detectors \
     /// Recursion ends on nodes with no children or nodes with a special type \
-    /// Returns a tuple of the transformed points and their ids \
+    /// `parent` is the transformation from the coordinates of the parent of the node to the coordinates of the instrument. \
+    /// The ids are assigned from the `idlist`s, see `get_detectors`.
+    pub fn recursive_detectors(
+        &self,
+        parent: Isometry3<f32>,
+        geometry: Geometry,
+    ) -> anyhow::Result<Vec<ExpandedDetector>> {
+        let mut mismatches = Vec::new();
+
+        let detectors = self.expand(&Placement::from(parent), geometry, &mut mismatches)?;
+
+        match mismatches.first() {
+            Some(mismatch) => Err(anyhow::anyhow!("{}", mismatch)),
+            None => Ok(detectors),
+        }
+    }
+
+    /// Returns every `idlist` below the node whose number of ids does not match the number of detectors
+    /// of the component that references it, or that does not exist at all. \
+    /// An empty list means `get_detectors` assigns all ids without errors. \
+    /// Errors if the tree can not be expanded.
+    /// # Example
+    /// ```
+    /// use mantid_idf::DetectorDefinition;
     ///
-    /// The ids don't have to be specified by any of the components &rarr; They are then typically defined in the `IDList` \
+    /// let content = r#"
+    ///     <instrument name="Test">
+    ///         <component type="pixel" idlist="ids">
+    ///             <locations x="0" x-end="1" n-elements="2"/>
+    ///         </component>
+    ///         <type name="pixel" is="detector"/>
+    ///         <idlist idname="ids"><id start="1" end="5" step="2"/></idlist>
+    ///     </instrument>
+    /// "#;
     ///
-    /// TODO: Add example + more explanation
-    pub fn recursive_transform_points(
+    /// let detector_definition = DetectorDefinition::from_str(content).unwrap();
+    ///
+    /// let mismatches = detector_definition.component_tree.validate_id_lists().unwrap();
+    ///
+    /// assert_eq!(mismatches.len(), 1);
+    /// assert_eq!(mismatches[0].ids, Some(3));
+    /// assert_eq!(mismatches[0].detectors, 2);
+    /// ```
+    pub fn validate_id_lists(&self) -> anyhow::Result<Vec<IDListMismatch>> {
+        let mut mismatches = Vec::new();
+
+        self.expand(
+            &Placement::from(Isometry3::identity()),
+            Geometry::Physical,
+            &mut mismatches,
+        )?;
+
+        Ok(mismatches)
+    }
+
+    /// Expands the node at every placement of the parent into its detectors. \
+    /// The ids of the `idlist`s are assigned, `idlist`s that do not match their detectors are added to `mismatches`. \
+    /// Errors if a special type can not be expanded, e.g. because of missing attributes.
+    fn expand(
         &self,
-        translations: Vec<Translation3<f32>>,
-        rotations: Vec<Rotation3<f32>>,
-    ) -> (Vec<Point>, Vec<u32>) {
-        let mut points = Vec::new();
-        let mut ids = Vec::new();
+        parent: &Placement<'_>,
+        geometry: Geometry,
+        mismatches: &mut Vec<IDListMismatch>,
+    ) -> anyhow::Result<Vec<ExpandedDetector>> {
+        let mut detectors = Vec::new();
 
         if self.component.is_root() {
-            let new_translations = vec![];
-            let new_rotations = vec![];
-
-            self.extend_points_with_children_points(
-                &mut points,
-                &mut ids,
-                new_translations,
-                new_rotations,
-                &translations,
-                &rotations,
-            );
+            for child in self.children.iter() {
+                detectors.extend(child.expand(
+                    &parent.without_exclusions(),
+                    geometry,
+                    mismatches,
+                )?);
+            }
 
-            return (points, ids);
+            return Ok(detectors);
         }
 
-        let no_children = self.children.is_empty();
-
         match self.get_special_type() {
             SpecialTypes::RectangularDetector => {
-                let type_ = self.get_type_name();
-
-                let xpixels = type_
-                    .other_attributes
-                    .get("xpixels")
-                    .unwrap()
-                    .parse::<u32>()
-                    .unwrap();
-
-                let xstart = type_
-                    .other_attributes
-                    .get("xstart")
-                    .unwrap()
-                    .parse::<f32>()
-                    .unwrap();
-
-                let xstep = type_
-                    .other_attributes
-                    .get("xstep")
-                    .unwrap()
-                    .parse::<f32>()
-                    .unwrap();
-
-                let ypixels = type_
-                    .other_attributes
-                    .get("ypixels")
-                    .unwrap()
-                    .parse::<u32>()
-                    .unwrap();
-
-                let ystart = type_
-                    .other_attributes
-                    .get("ystart")
-                    .unwrap()
-                    .parse::<f32>()
-                    .unwrap();
-
-                let ystep = type_
-                    .other_attributes
-                    .get("ystep")
-                    .unwrap()
-                    .parse::<f32>()
-                    .unwrap();
-
-                let idfillbyfirst = self
-                    .component
-                    .other_attributes
-                    .get("idfillbyfirst")
-                    .unwrap()
-                    .parse::<Axes>()
-                    .unwrap();
-
-                let idstart = self
-                    .component
-                    .other_attributes
-                    .get("idstart")
-                    .unwrap()
-                    .parse::<u32>()
-                    .unwrap();
+                let rectangular_detector = RectangularDetector::from_type_and_component(
+                    self.get_type_name(),
+                    &self.component,
+                )
+                .context(format!(
+                    "could not expand rectangular detector {}",
+                    self.component.type_name
+                ))?;
+
+                self.check_pixel_type(&rectangular_detector.pixel_type)?;
+
+                let pixels = rectangular_detector
+                    .pixels(&self.defaults)
+                    .collect::<Vec<_>>();
+
+                for placement in self.placements(parent, geometry) {
+                    detectors.extend(pixels.iter().map(|(id, point)| ExpandedDetector {
+                        id: Some(*id),
+                        transformation: placement.transformation * Translation3::from(point.coords),
+                        special_type: SpecialTypes::RectangularDetector,
+                        type_name: rectangular_detector.pixel_type.clone(),
+                        corners: None,
+                    }));
+                }
+            }
+            SpecialTypes::StructuredDetector => {
+                let structured_detector = StructuredDetector::from_type_and_component(
+                    self.get_type_name(),
+                    &self.component,
+                )
+                .context(format!(
+                    "could not expand structured detector {}",
+                    self.component.type_name
+                ))?;
 
-                let idstepbyrow = self
-                    .component
-                    .other_attributes
-                    .get("idstepbyrow")
-                    .unwrap()
-                    .parse::<u32>()
-                    .unwrap();
+                self.check_pixel_type(&structured_detector.pixel_type)?;
 
-                let mut untransformed_points = Vec::new();
-                let mut ids_ = Vec::new();
+                let pixels = structured_detector
+                    .pixels(&self.defaults)
+                    .collect::<Vec<_>>();
 
-                let mut id = idstart;
+                for placement in self.placements(parent, geometry) {
+                    let transformation = placement.transformation;
 
-                for x in 0..xpixels {
-                    for y in 0..ypixels {
-                        let x = xstart + x as f32 * xstep;
-                        let y = ystart + y as f32 * ystep;
+                    detectors.extend(pixels.iter().map(|pixel| ExpandedDetector {
+                        id: Some(pixel.id),
+                        transformation: transformation * Translation3::from(pixel.centre.coords),
+                        special_type: SpecialTypes::StructuredDetector,
+                        type_name: structured_detector.pixel_type.clone(),
+                        corners: Some(pixel.corners.map(|corner| transformation * corner)),
+                    }));
+                }
+            }
+            SpecialTypes::GridDetector => {
+                let grid_detector =
+                    GridDetector::from_type_and_component(self.get_type_name(), &self.component)
+                        .context(format!(
+                            "could not expand grid detector {}",
+                            self.component.type_name
+                        ))?;
+
+                self.check_pixel_type(&grid_detector.pixel_type)?;
+
+                let voxels = grid_detector.voxels(&self.defaults).collect::<Vec<_>>();
+
+                for placement in self.placements(parent, geometry) {
+                    detectors.extend(voxels.iter().map(|(id, point)| ExpandedDetector {
+                        id: Some(*id),
+                        transformation: placement.transformation * Translation3::from(point.coords),
+                        special_type: SpecialTypes::GridDetector,
+                        type_name: grid_detector.pixel_type.clone(),
+                        corners: None,
+                    }));
+                }
+            }
+            special_type @ (SpecialTypes::Detector | SpecialTypes::Monitor) => {
+                detectors.extend(
+                    self.placements(parent, geometry)
+                        .into_iter()
+                        .map(|placement| ExpandedDetector {
+                            id: None,
+                            transformation: placement.transformation,
+                            special_type: special_type.clone(),
+                            type_name: self.component.type_name.clone(),
+                            corners: None,
+                        }),
+                );
+            }
+            SpecialTypes::None => {
+                let placements = match self.component.location.is_empty()
+                    && self.component.locations.is_empty()
+                {
+                    true => match self.children.is_empty() {
+                        true => {
+                            unreachable!(
+                                "Node has no locations, no children and no special type: {:?}",
+                                self
+                            );
+                        }
+                        false => vec![parent.without_exclusions()],
+                    },
+                    false => self.placements(parent, geometry),
+                };
+
+                for placement in placements.iter() {
+                    for child in self.children.iter() {
+                        detectors.extend(child.expand(placement, geometry, mismatches)?);
+                    }
+                }
+            }
+            _ => (),
+        }
 
-                        let point = Point::new(x, y, 0.0);
+        if let Some(idlist) = self.component.idlist.as_ref() {
+            if let Err(mismatch) = self.assign_ids(idlist, &mut detectors) {
+                mismatches.push(mismatch);
+            }
+        }
 
-                        untransformed_points.push(point);
-                        ids_.push(id);
+        Ok(detectors)
+    }
 
-                        id = match idfillbyfirst {
-                            Axes::X => id + idstepbyrow,
-                            Axes::Y => id + 1,
-                            _ => unreachable!("idfillbyfirst should be either X or Y"),
-                        };
-                    }
+    /// Errors if the pixel type of a rectangular, structured or grid detector does not exist.
+    fn check_pixel_type(&self, pixel_type: &str) -> anyhow::Result<()> {
+        match self.types.contains_key(pixel_type) {
+            true => Ok(()),
+            false => Err(anyhow::anyhow!(
+                "could not find pixel type {} of detector {}",
+                pixel_type,
+                self.component.type_name
+            )),
+        }
+    }
 
-                    id = match idfillbyfirst {
-                        Axes::X => id + idstepbyrow,
-                        Axes::Y => id + 1,
-                        _ => unreachable!("idfillbyfirst should be either X or Y"),
-                    };
+    /// Assigns the ids of the `idlist` to the detectors that do not have an id yet. \
+    /// No ids are assigned if the number of ids does not match.
+    fn assign_ids(
+        &self,
+        idlist: &str,
+        detectors: &mut [ExpandedDetector],
+    ) -> Result<(), IDListMismatch> {
+        let mut without_id = detectors
+            .iter_mut()
+            .filter(|detector| detector.id.is_none())
+            .collect::<Vec<_>>();
+
+        let id_list = self.id_lists.get(idlist);
+
+        let ids = id_list.map(IDList::len);
+
+        match id_list {
+            Some(id_list) if id_list.len() == without_id.len() => {
+                for (detector, id) in without_id.iter_mut().zip(id_list.iter()) {
+                    detector.id = Some(id);
                 }
 
-                for location in self.component.location.iter() {
-                    let (new_translations, new_rotations) =
-                        location.to_new_translations_and_rotations();
+                Ok(())
+            }
+            _ => Err(IDListMismatch {
+                idlist: idlist.to_string(),
+                component: self.component.type_name.clone(),
+                ids,
+                detectors: without_id.len(),
+            }),
+        }
+    }
 
-                    untransformed_points.iter_mut().for_each(|point| {
-                        for translation in new_translations.iter() {
-                            *point = translation.transform_point(point);
-                        }
+    /// Returns the placements of all `location`s and all elements of the `locations` of the node
+    /// in the coordinates of the instrument. \
+    /// The `facing` of the locations (or the `components-are-facing` default) is applied. \
+    /// Locations whose name is excluded by the parent are skipped.
+    fn placements<'a>(&'a self, parent: &Placement<'_>, geometry: Geometry) -> Vec<Placement<'a>> {
+        let mut placements = Vec::new();
+
+        for location in self.component.location.iter() {
+            if parent
+                .exclude
+                .contains(&self.component.location_name(location))
+            {
+                continue;
+            }
 
-                        for rotation in new_rotations.iter() {
-                            *point = rotation.transform_point(point);
-                        }
-                    });
+            let placed_location = match (geometry, location.neutronic.as_deref()) {
+                (Geometry::Neutronic, Some(neutronic)) => neutronic,
+                _ => location,
+            };
+
+            let mut placement = parent.place(
+                placed_location.to_isometry(&self.defaults),
+                placed_location.spherical_offset(&self.defaults),
+                &self.defaults,
+            );
+
+            placement.transformation =
+                placed_location.apply_facing(placement.transformation, &self.defaults);
+            placement.exclude = &location.exclude;
 
-                    points.extend(untransformed_points.clone());
-                    ids.extend(ids_.clone());
+            placements.push(placement);
+        }
+
+        for locations in self.component.locations.iter() {
+            for element in 0..locations.n_elements {
+                if parent
+                    .exclude
+                    .contains(&self.component.locations_element_name(locations, element))
+                {
+                    continue;
                 }
+
+                let mut placement = parent.place(
+                    locations.to_isometry(element, &self.defaults),
+                    locations.spherical_offset(element, &self.defaults),
+                    &self.defaults,
+                );
+
+                placement.transformation =
+                    apply_facing(placement.transformation, None, &self.defaults);
+
+                placements.push(placement);
             }
-            SpecialTypes::Detector => {
-                for location in self.component.location.iter() {
-                    let (new_translations, new_rotations) =
-                        location.to_new_translations_and_rotations();
+        }
 
-                    let mut point = Point::default();
+        placements
+    }
+}
 
-                    for translation in new_translations.iter() {
-                        point = translation.transform_point(&point);
-                    }
+/// The resolved position parameters of a single location, see `ComponentTreeNode::apply_position_parameters`.
+#[derive(Debug, Default, Clone, Copy)]
+struct PositionParameters {
+    /// `x`, `y` and `z` in metres.
+    cartesian: [Option<f32>; 3],
+    /// `r-position` in metres, `t-position` and `p-position` in degrees.
+    spherical: [Option<f32>; 3],
+    /// `rotx`, `roty` and `rotz` in degrees.
+    rotation: [Option<f32>; 3],
+}
 
-                    for rotation in new_rotations.iter() {
-                        point = rotation.transform_point(&point);
-                    }
+impl PositionParameters {
+    fn is_moved(&self) -> bool {
+        self.cartesian
+            .iter()
+            .chain(self.spherical.iter())
+            .any(Option::is_some)
+    }
 
-                    points.push(point);
-                }
+    fn is_rotated(&self) -> bool {
+        self.rotation.iter().any(Option::is_some)
+    }
 
-                for locations in self.component.locations.iter() {
-                    for element in 0..locations.n_elements {
-                        let (new_translations, new_rotations) =
-                            locations.to_new_translations_and_rotations(element);
+    fn is_set(&self) -> bool {
+        self.is_moved() || self.is_rotated()
+    }
 
-                        let mut point = Point::default();
+    /// Replaces the translation and rotation of the location.
+    fn apply(&self, location: &mut Location, defaults: &Defaults) {
+        if !self.is_set() {
+            return;
+        }
 
-                        for translation in new_translations.iter() {
-                            point = translation.transform_point(&point);
-                        }
+        // The nested `rot`/`trans` tags are folded into the new attributes of the location
+        let isometry = location.to_isometry(defaults);
 
-                        for rotation in new_rotations.iter() {
-                            point = rotation.transform_point(&point);
-                        }
+        let mut position = Point::from(isometry.translation.vector);
+        let mut rotation = isometry.rotation;
 
-                        points.push(point);
-                    }
-                }
-            }
-            SpecialTypes::None => {
-                if self.component.location.is_empty() && self.component.locations.is_empty() {
-                    match no_children {
-                        true => {
-                            unreachable!(
-                                "Node has no locations, no children and no special type: {:?}",
-                                self
-                            );
-                        }
-                        false => {
-                            let new_translations = vec![nalgebra::Translation3::identity()];
-                            let new_rotations = vec![nalgebra::Rotation3::identity()];
-
-                            self.extend_points_with_children_points(
-                                &mut points,
-                                &mut ids,
-                                new_translations,
-                                new_rotations,
-                                &translations,
-                                &rotations,
-                            );
+        for (coordinate, value) in position.iter_mut().zip(self.cartesian) {
+            *coordinate = value.unwrap_or(*coordinate);
+        }
 
-                            return (points, ids);
-                        }
-                    }
-                }
+        if self.spherical.iter().any(Option::is_some) {
+            let [r, t, p] = self.spherical;
+            let spherical = cartesian_to_spherical(position);
 
-                for location in self.component.location.iter() {
-                    let (new_translations, new_rotations) =
-                        location.to_new_translations_and_rotations();
-
-                    self.extend_points_with_children_points(
-                        &mut points,
-                        &mut ids,
-                        new_translations,
-                        new_rotations,
-                        &translations,
-                        &rotations,
-                    );
-                }
+            position = spherical_to_cartesian(Point::new(
+                r.unwrap_or(spherical.x),
+                t.map(f32::to_radians).unwrap_or(spherical.y),
+                p.map(f32::to_radians).unwrap_or(spherical.z),
+            ));
+        }
 
-                for locations in self.component.locations.iter() {
-                    for element in 0..locations.n_elements {
-                        let (new_translations, new_rotations) =
-                            locations.to_new_translations_and_rotations(element);
-
-                        self.extend_points_with_children_points(
-                            &mut points,
-                            &mut ids,
-                            new_translations,
-                            new_rotations,
-                            &translations,
-                            &rotations,
-                        );
-                    }
-                }
-            }
-            _ => (),
+        if self.is_rotated() {
+            rotation = UnitQuaternion::from_rotation_matrix(
+                &[Axes::X, Axes::Y, Axes::Z].iter().zip(self.rotation).fold(
+                    Rotation3::identity(),
+                    |rotation, (axis, angle)| {
+                        rotation
+                            * Rotation3::from_axis_angle(
+                                &nalgebra::Unit::new_unchecked(axis.unit_vector()),
+                                angle.unwrap_or_default().to_radians(),
+                            )
+                    },
+                ),
+            );
         }
 
-        (points, ids)
+        location.translation = vec![Translation::Cartesian(
+            position.map(|length| defaults.metres_to_length(length)),
+        )];
+
+        location.rotation = match rotation.axis_angle() {
+            Some((axis, angle)) => vec![Rotation {
+                rot: defaults.radians_to_angle(angle),
+                axis: Point::from(axis.into_inner()),
+            }],
+            None => Vec::new(),
+        };
+
+        location.nested.clear();
+    }
+}
+
+/// A placement of a component during the expansion of the tree, passed on to the children of the component.
+#[derive(Debug, Clone, Copy)]
+struct Placement<'a> {
+    /// The transformation from the coordinates of the component to the coordinates of the instrument.
+    transformation: Isometry3<f32>,
+    /// The spherical coordinates of the component (metres and radians), used for `offsets spherical="delta"`.
+    spherical: Point,
+    /// The names of the sub-parts that the location of the component leaves out.
+    exclude: &'a [String],
+}
+
+impl From<Isometry3<f32>> for Placement<'_> {
+    fn from(transformation: Isometry3<f32>) -> Self {
+        Self {
+            transformation,
+            spherical: cartesian_to_spherical(Point::from(transformation.translation.vector)),
+            exclude: &[],
+        }
     }
+}
 
-    fn extend_points_with_children_points(
+impl<'a> Placement<'a> {
+    fn without_exclusions(&self) -> Placement<'a> {
+        Placement {
+            exclude: &[],
+            ..*self
+        }
+    }
+
+    /// Places a child with the transformation `local` relative to this placement. \
+    /// `spherical_offset` is the spherical translation of the location of the child (metres and radians).
+    /// With `offsets spherical="delta"` it is added to the spherical coordinates accumulated along the parents
+    /// (Mantid keeps them in `m_tempPosHolder`), instead of being converted to a cartesian translation relative to
+    /// the parent. The angles of a parent at the origin are therefore kept.
+    fn place<'b>(
         &self,
-        points: &mut Vec<Point>,
-        ids: &mut Vec<u32>,
-        new_translations: Vec<Translation3<f32>>,
-        new_rotations: Vec<Rotation3<f32>>,
-        translations: &Vec<Translation3<f32>>,
-        rotations: &Vec<Rotation3<f32>>,
-    ) {
-        for child in self.children.iter() {
-            let (mut child_points, child_ids) =
-                child.recursive_transform_points(new_translations.clone(), new_rotations.clone());
+        local: Isometry3<f32>,
+        spherical_offset: Option<Point>,
+        defaults: &Defaults,
+    ) -> Placement<'b> {
+        let (transformation, spherical) = match (defaults.offsets.spherical, spherical_offset) {
+            (SphericalOffsets::Delta, Some(offset)) => {
+                let spherical = self.spherical + offset.coords;
+
+                // The rotations and cartesian translations of the child stay relative to the parent
+                let relative =
+                    Translation3::from(spherical_to_cartesian(offset).coords).inverse() * local;
+
+                let transformation = Translation3::from(spherical_to_cartesian(spherical).coords)
+                    * self.transformation.rotation
+                    * relative;
+
+                (transformation, spherical)
+            }
+            _ => {
+                let transformation = self.transformation * local;
 
-            child_points.iter_mut().for_each(|p| {
-                for translation in translations.iter() {
-                    *p = translation.transform_point(&p);
-                }
+                let spherical =
+                    cartesian_to_spherical(Point::from(transformation.translation.vector));
 
-                for rotation in rotations.iter() {
-                    *p = rotation.transform_point(&p);
-                }
-            });
+                (transformation, spherical)
+            }
+        };
 
-            points.extend(child_points);
-            ids.extend(child_ids);
+        Placement {
+            transformation,
+            spherical,
+            exclude: &[],
         }
     }
 }
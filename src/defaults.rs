@@ -0,0 +1,325 @@
+//! Module for the `defaults` block of the instrument definition file. \
+//! The defaults define the units, the reference frame and a few global settings of the instrument. \
+//! https://docs.mantidproject.org/nightly/concepts/InstrumentDefinitionFile.html#using-defaults
+
+use nalgebra::Vector3;
+
+use crate::{structs::Translation, utils::Axes};
+
+/// Represents the `defaults` tag in the IDF file. \
+/// Every field falls back to the Mantid default if it is not specified.
+#[derive(Debug, Default, Clone)]
+pub struct Defaults {
+    /// The unit of all lengths in the file. Represents the `length` tag.
+    pub length_unit: LengthUnit,
+    /// The unit of all angles in the file. Represents the `angle` tag.
+    pub angle_unit: AngleUnit,
+    /// Defines which axis is along the beam and which axis is pointing up.
+    pub reference_frame: ReferenceFrame,
+    /// The view the instrument is initially displayed in.
+    pub default_view: Option<DefaultView>,
+    /// If present, all components are rotated to face this location. \
+    /// Represents the `components-are-facing` tag.
+    pub components_are_facing: Option<Translation>,
+    /// How the offsets of nested components are interpreted.
+    pub offsets: Offsets,
+}
+
+impl Defaults {
+    /// Converts a length in the length unit of the IDF file to metres.
+    pub fn length_to_metres(&self, length: f32) -> f32 {
+        self.length_unit.to_metres(length)
+    }
+
+    /// Converts an angle in the angle unit of the IDF file to radians.
+    pub fn angle_to_radians(&self, angle: f32) -> f32 {
+        self.angle_unit.to_radians(angle)
+    }
+
+    /// Converts a length in metres to the length unit of the IDF file.
+    pub fn metres_to_length(&self, metres: f32) -> f32 {
+        metres / self.length_unit.to_metres(1.0)
+    }
+
+    /// Converts an angle in radians to the angle unit of the IDF file.
+    pub fn radians_to_angle(&self, radians: f32) -> f32 {
+        radians / self.angle_unit.to_radians(1.0)
+    }
+}
+
+/// The unit of the lengths in the IDF file.
+#[allow(missing_docs)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LengthUnit {
+    #[default]
+    Metre,
+    Centimetre,
+    Millimetre,
+}
+
+impl LengthUnit {
+    /// Converts a length in this unit to metres.
+    pub fn to_metres(&self, length: f32) -> f32 {
+        match self {
+            Self::Metre => length,
+            Self::Centimetre => length * 1e-2,
+            Self::Millimetre => length * 1e-3,
+        }
+    }
+}
+
+impl std::str::FromStr for LengthUnit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "metre" | "meter" | "metres" | "meters" | "m" => Ok(Self::Metre),
+            "centimetre" | "centimeter" | "centimetres" | "centimeters" | "cm" => {
+                Ok(Self::Centimetre)
+            }
+            "millimetre" | "millimeter" | "millimetres" | "millimeters" | "mm" => {
+                Ok(Self::Millimetre)
+            }
+            _ => Err(anyhow::anyhow!(
+                "could not parse string: {:?} to LengthUnit",
+                s
+            )),
+        }
+    }
+}
+
+/// The unit of the angles in the IDF file.
+#[allow(missing_docs)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AngleUnit {
+    #[default]
+    Degree,
+    Radian,
+}
+
+impl AngleUnit {
+    /// Converts an angle in this unit to radians.
+    pub fn to_radians(&self, angle: f32) -> f32 {
+        match self {
+            Self::Degree => angle.to_radians(),
+            Self::Radian => angle,
+        }
+    }
+}
+
+impl std::str::FromStr for AngleUnit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "degree" | "degrees" | "deg" => Ok(Self::Degree),
+            "radian" | "radians" | "rad" => Ok(Self::Radian),
+            _ => Err(anyhow::anyhow!(
+                "could not parse string: {:?} to AngleUnit",
+                s
+            )),
+        }
+    }
+}
+
+/// Represents the `reference-frame` tag in the IDF file. \
+/// The default is a right-handed frame with the beam along `z` and `y` pointing up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferenceFrame {
+    /// The axis along the beam. Represents the `along-beam` tag.
+    pub along_beam: Axes,
+    /// The axis pointing up. Represents the `pointing-up` tag.
+    pub pointing_up: Axes,
+    /// The handedness of the coordinate system.
+    pub handedness: Handedness,
+    /// The axis that defines the sign of the scattering angle. \
+    /// Represents the `theta-sign` tag. Mantid uses the `pointing-up` axis if not specified.
+    pub theta_sign: Option<Axes>,
+}
+
+impl Default for ReferenceFrame {
+    fn default() -> Self {
+        Self {
+            along_beam: Axes::Z,
+            pointing_up: Axes::Y,
+            handedness: Handedness::Right,
+            theta_sign: None,
+        }
+    }
+}
+
+impl ReferenceFrame {
+    /// Unit vector pointing along the beam.
+    /// # Example
+    /// ```
+    /// use mantid_idf::defaults::ReferenceFrame;
+    ///
+    /// let reference_frame = ReferenceFrame::default();
+    ///
+    /// assert_eq!(reference_frame.beam_direction(), nalgebra::Vector3::z());
+    /// ```
+    pub fn beam_direction(&self) -> Vector3<f32> {
+        self.along_beam.unit_vector()
+    }
+
+    /// Unit vector pointing up.
+    pub fn up_direction(&self) -> Vector3<f32> {
+        self.pointing_up.unit_vector()
+    }
+
+    /// Unit vector of the horizontal axis, perpendicular to the beam and the up direction. \
+    /// Its sign follows from the handedness of the reference frame.
+    /// # Example
+    /// ```
+    /// use mantid_idf::defaults::ReferenceFrame;
+    ///
+    /// let reference_frame = ReferenceFrame::default();
+    ///
+    /// assert_eq!(reference_frame.horizontal_direction(), nalgebra::Vector3::x());
+    /// ```
+    pub fn horizontal_direction(&self) -> Vector3<f32> {
+        let horizontal = self.up_direction().cross(&self.beam_direction());
+
+        match self.handedness {
+            Handedness::Right => horizontal,
+            Handedness::Left => -horizontal,
+        }
+    }
+
+    /// The axis that defines the sign of the scattering angle.
+    pub fn theta_sign_axis(&self) -> Axes {
+        self.theta_sign.unwrap_or(self.pointing_up)
+    }
+}
+
+/// The handedness of the reference frame.
+#[allow(missing_docs)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Handedness {
+    #[default]
+    Right,
+    Left,
+}
+
+impl std::str::FromStr for Handedness {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "right" => Ok(Self::Right),
+            "left" => Ok(Self::Left),
+            _ => Err(anyhow::anyhow!(
+                "could not parse string: {:?} to Handedness",
+                s
+            )),
+        }
+    }
+}
+
+/// Represents the `default-view` tag in the IDF file.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DefaultView {
+    /// The type of view.
+    pub view: View,
+    /// The axis the 3D view is looking along. Represents the `axis-view` attribute.
+    pub axis_view: AxisView,
+}
+
+/// The different views of the Mantid instrument view.
+#[allow(missing_docs)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    #[default]
+    ThreeD,
+    CylindricalX,
+    CylindricalY,
+    CylindricalZ,
+    SphericalX,
+    SphericalY,
+    SphericalZ,
+    SideBySide,
+}
+
+impl std::str::FromStr for View {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "3D" | "3d" => Ok(Self::ThreeD),
+            "cylindrical_x" => Ok(Self::CylindricalX),
+            "cylindrical_y" => Ok(Self::CylindricalY),
+            "cylindrical_z" => Ok(Self::CylindricalZ),
+            "spherical_x" => Ok(Self::SphericalX),
+            "spherical_y" => Ok(Self::SphericalY),
+            "spherical_z" => Ok(Self::SphericalZ),
+            "side_by_side" => Ok(Self::SideBySide),
+            _ => Err(anyhow::anyhow!("could not parse string: {:?} to View", s)),
+        }
+    }
+}
+
+/// The axis (and direction) the 3D view is looking along, e.g. `z-`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AxisView {
+    /// The axis the view is looking along.
+    pub axis: Axes,
+    /// `true` if the view is looking along the positive direction of the axis.
+    pub positive: bool,
+}
+
+impl Default for AxisView {
+    fn default() -> Self {
+        Self {
+            axis: Axes::Z,
+            positive: true,
+        }
+    }
+}
+
+impl std::str::FromStr for AxisView {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (axis, positive) = match s.strip_suffix('-') {
+            Some(axis) => (axis, false),
+            None => (s.strip_suffix('+').unwrap_or(s), true),
+        };
+
+        Ok(Self {
+            axis: axis.to_lowercase().parse()?,
+            positive,
+        })
+    }
+}
+
+/// Represents the `offsets` tag in the IDF file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Offsets {
+    /// How spherical coordinates of nested components are interpreted.
+    pub spherical: SphericalOffsets,
+}
+
+/// How spherical coordinates of nested components are interpreted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SphericalOffsets {
+    /// The spherical coordinates are converted to a cartesian translation relative to the parent.
+    #[default]
+    Absolute,
+    /// The spherical coordinates are added to the spherical coordinates of the parent.
+    Delta,
+}
+
+impl std::str::FromStr for SphericalOffsets {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "absolute" => Ok(Self::Absolute),
+            "delta" => Ok(Self::Delta),
+            _ => Err(anyhow::anyhow!(
+                "could not parse string: {:?} to SphericalOffsets",
+                s
+            )),
+        }
+    }
+}
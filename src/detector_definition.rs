@@ -5,12 +5,13 @@ use std::{collections::BTreeMap, sync::Arc};
 use anyhow::Context;
 
 use crate::{
-    component_tree::ComponentTree, idlists::IDList, types::Types,
+    component_tree::ComponentTree, defaults::Defaults, idlists::IDList,
+    parameter_file::ParameterFile, sample_logs::SampleLogs, structs::Instrument, types::Types,
     xml_parser::detector_definition_from_str,
 };
 
 /// Main instrument definition struct. \
-/// Contains the types and the component tree. \
+/// Contains the types, the component tree and the defaults. \
 ///
 /// https://docs.mantidproject.org/nightly/concepts/InstrumentDefinitionFile.html \
 /// The instrument definition file is structured as a combination of components and types. \
@@ -31,17 +32,97 @@ use crate::{
 /// ```
 #[derive(Debug, Default)]
 pub struct DetectorDefinition {
+    /// The name and the period of validity of the instrument.
+    pub instrument: Instrument,
     /// Pointer to the types for lookup.
     pub types: Arc<Types>,
     /// The component tree.
     pub component_tree: ComponentTree,
     /// Potential ID lists.
-    pub id_lists: BTreeMap<String, IDList>,
+    pub id_lists: Arc<BTreeMap<String, IDList>>,
+    /// The units, reference frame and other global settings of the instrument.
+    pub defaults: Arc<Defaults>,
+    /// The names of the `component-link`s inside of the IDF that did not match any component,
+    /// like `apply_parameter_file` returns them for parameter files.
+    pub unmatched_component_links: Vec<String>,
 }
 
 impl DetectorDefinition {
     /// Parse the detector definition from a string.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(str: &str) -> anyhow::Result<Self> {
         detector_definition_from_str(str).context("could not parse detector definition")
     }
+
+    /// Attaches the parameters of a parameter file to the matching components of the component tree. \
+    /// A component link with the name of the instrument refers to the whole instrument. \
+    /// Returns the names of the component links that did not match any component.
+    /// # Example
+    /// ```
+    /// use mantid_idf::{parameter_file::ParameterFile, DetectorDefinition};
+    ///
+    /// let content = std::fs::read_to_string("assets/test_detector_definition.xml").unwrap();
+    ///
+    /// let mut detector_definition = DetectorDefinition::from_str(&content).unwrap();
+    ///
+    /// let parameter_file = ParameterFile::from_str(r#"
+    ///     <parameter-file instrument="Test">
+    ///         <component-link name="VoxelsRoot">
+    ///             <parameter name="TubePressure"><value val="10.0"/></parameter>
+    ///         </component-link>
+    ///         <component-link name="DoesNotExist">
+    ///             <parameter name="TubePressure"><value val="5.0"/></parameter>
+    ///         </component-link>
+    ///     </parameter-file>
+    /// "#).unwrap();
+    ///
+    /// let unmatched = detector_definition.apply_parameter_file(&parameter_file);
+    ///
+    /// assert_eq!(unmatched, ["DoesNotExist"]);
+    /// ```
+    pub fn apply_parameter_file(&mut self, parameter_file: &ParameterFile) -> Vec<String> {
+        parameter_file
+            .component_links
+            .iter()
+            .filter(|component_link| {
+                !self
+                    .component_tree
+                    .apply_component_link(component_link, &self.instrument.name)
+            })
+            .map(|component_link| component_link.name.clone())
+            .collect()
+    }
+
+    /// Positions the components according to the sample logs of a run. \
+    /// Components with position parameters (`x`, `y`, `z`, `r-position`, `t-position`, `p-position`,
+    /// `rotx`, `roty`, `rotz`) are moved and rotated, e.g. motor driven detector banks.
+    /// # Example
+    /// ```
+    /// use mantid_idf::{sample_logs::{SampleLogs, TimeSeries}, DetectorDefinition};
+    ///
+    /// let content = r#"
+    ///     <instrument name="Test">
+    ///         <component type="pixel">
+    ///             <location z="1.0"/>
+    ///             <parameter name="x"><logfile id="motor" extract-single-value-as="last_value"/></parameter>
+    ///         </component>
+    ///         <type name="pixel" is="detector"/>
+    ///     </instrument>
+    /// "#;
+    ///
+    /// let mut detector_definition = DetectorDefinition::from_str(content).unwrap();
+    ///
+    /// let sample_logs = SampleLogs::from([("motor".to_string(), TimeSeries::from_values(vec![0.5, 2.0]))]);
+    ///
+    /// detector_definition.apply_sample_logs(&sample_logs).unwrap();
+    ///
+    /// let points = detector_definition.component_tree.get_special_type_points().unwrap();
+    ///
+    /// assert_eq!(points, [mantid_idf::Point::new(2.0, 0.0, 1.0)]);
+    /// ```
+    pub fn apply_sample_logs(&mut self, sample_logs: &SampleLogs) -> anyhow::Result<()> {
+        self.component_tree
+            .apply_sample_logs(sample_logs)
+            .context("could not apply sample logs")
+    }
 }
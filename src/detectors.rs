@@ -0,0 +1,493 @@
+//! Models of the detectors that are generated from a few attributes instead of listing every pixel. \
+//! https://docs.mantidproject.org/nightly/concepts/InstrumentDefinitionFile.html#creating-rectangular-area-detectors \
+//! https://docs.mantidproject.org/nightly/concepts/InstrumentDefinitionFile.html#creating-structured-detectors \
+//! https://docs.mantidproject.org/nightly/concepts/InstrumentDefinitionFile.html#creating-grid-detectors
+
+use std::{collections::BTreeMap, str::FromStr};
+
+use anyhow::Context;
+use nalgebra::{Point2, Vector3};
+
+use crate::{
+    defaults::Defaults,
+    structs::{Component, Type},
+    utils::Axes,
+    Point,
+};
+
+/// The numbering of the pixels of a two dimensional detector. \
+/// Defined by the `idstart`, `idfillbyfirst`, `idstep` and `idstepbyrow` attributes of the component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PixelIDs {
+    /// The id of the first pixel. Defaults to 0.
+    pub idstart: i64,
+    /// The axis along which the ids are filled first. Defaults to y.
+    pub idfillbyfirst: Axes,
+    /// The difference between the ids of two neighbouring pixels along `idfillbyfirst`. Defaults to 1.
+    pub idstep: i64,
+    /// The difference between the ids of two neighbouring rows.
+    /// Defaults to the number of pixels along `idfillbyfirst`.
+    pub idstepbyrow: i64,
+}
+
+impl PixelIDs {
+    /// Reads the id attributes of the component of a detector with `xpixels` times `ypixels` pixels.
+    pub fn from_component(
+        component: &Component,
+        xpixels: usize,
+        ypixels: usize,
+    ) -> anyhow::Result<Self> {
+        let attributes = &component.other_attributes;
+
+        let idfillbyfirst = optional_attribute(attributes, "idfillbyfirst", &component.type_name)?
+            .unwrap_or(Axes::Y);
+
+        let default_idstepbyrow = match idfillbyfirst {
+            Axes::X => xpixels as i64,
+            Axes::Y => ypixels as i64,
+            Axes::Z => {
+                return Err(anyhow::anyhow!(
+                    "idfillbyfirst of component {} has to be x or y",
+                    component.type_name
+                ))
+            }
+        };
+
+        Ok(Self {
+            idstart: optional_attribute(attributes, "idstart", &component.type_name)?.unwrap_or(0),
+            idfillbyfirst,
+            idstep: optional_attribute(attributes, "idstep", &component.type_name)?.unwrap_or(1),
+            idstepbyrow: optional_attribute(attributes, "idstepbyrow", &component.type_name)?
+                .unwrap_or(default_idstepbyrow),
+        })
+    }
+
+    /// The id of the pixel with the index `x` along x and `y` along y.
+    pub fn id(&self, x: usize, y: usize) -> i64 {
+        let (x, y) = (x as i64, y as i64);
+
+        match self.idfillbyfirst {
+            Axes::X => self.idstart + y * self.idstepbyrow + x * self.idstep,
+            _ => self.idstart + x * self.idstepbyrow + y * self.idstep,
+        }
+    }
+}
+
+/// A rectangular grid of pixels in the xy-plane of the detector. \
+/// The geometry is defined by the attributes of the `RectangularDetector` type,
+/// the ids by the attributes of the component that uses the type.
+/// # Example
+/// ```
+/// use mantid_idf::{defaults::Defaults, detectors::RectangularDetector, structs::{Component, Type}};
+///
+/// let mut type_ = Type::default();
+/// let mut component = Component::default();
+///
+/// for (key, value) in [("xpixels", "2"), ("xstart", "0"), ("xstep", "1"), ("ypixels", "3"), ("ystart", "0"), ("ystep", "1"), ("type", "pixel")] {
+///     type_.other_attributes.insert(key.to_string(), value.to_string());
+/// }
+///
+/// component.other_attributes.insert("idstart".to_string(), "100".to_string());
+///
+/// let detector = RectangularDetector::from_type_and_component(&type_, &component).unwrap();
+///
+/// // The ids are filled along y first, one row of y has 3 pixels
+/// assert_eq!(detector.ids.id(0, 2), 102);
+/// assert_eq!(detector.ids.id(1, 0), 103);
+/// assert_eq!(detector.pixels(&Defaults::default()).count(), 6);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RectangularDetector {
+    /// The number of pixels along x.
+    pub xpixels: usize,
+    /// The x coordinate of the first pixel.
+    pub xstart: f32,
+    /// The distance between two pixels along x.
+    pub xstep: f32,
+    /// The number of pixels along y.
+    pub ypixels: usize,
+    /// The y coordinate of the first pixel.
+    pub ystart: f32,
+    /// The distance between two pixels along y.
+    pub ystep: f32,
+    /// The type of the pixels. Its shape is the shape of every pixel.
+    pub pixel_type: String,
+    /// The numbering of the pixels.
+    pub ids: PixelIDs,
+}
+
+impl RectangularDetector {
+    /// Reads the geometry from the attributes of the type and the ids from the attributes of the component. \
+    /// Errors if a required attribute is missing or can not be parsed.
+    pub fn from_type_and_component(type_: &Type, component: &Component) -> anyhow::Result<Self> {
+        let attributes = &type_.other_attributes;
+
+        let xpixels = required_attribute(attributes, "xpixels", &type_.name)?;
+        let ypixels = required_attribute(attributes, "ypixels", &type_.name)?;
+
+        Ok(Self {
+            xpixels,
+            xstart: required_attribute(attributes, "xstart", &type_.name)?,
+            xstep: required_attribute(attributes, "xstep", &type_.name)?,
+            ypixels,
+            ystart: required_attribute(attributes, "ystart", &type_.name)?,
+            ystep: required_attribute(attributes, "ystep", &type_.name)?,
+            pixel_type: required_attribute(attributes, "type", &type_.name)?,
+            ids: PixelIDs::from_component(component, xpixels, ypixels)?,
+        })
+    }
+
+    /// The centre of the pixel with the index `x` along x and `y` along y in metres,
+    /// in the coordinates of the detector.
+    pub fn pixel_position(&self, x: usize, y: usize, defaults: &Defaults) -> Point {
+        Point::new(
+            defaults.length_to_metres(self.xstart + x as f32 * self.xstep),
+            defaults.length_to_metres(self.ystart + y as f32 * self.ystep),
+            0.0,
+        )
+    }
+
+    /// The ids and positions of all pixels. x is the outer and y the inner index, like in Mantid.
+    pub fn pixels<'a>(&'a self, defaults: &'a Defaults) -> impl Iterator<Item = (i64, Point)> + 'a {
+        (0..self.xpixels).flat_map(move |x| {
+            (0..self.ypixels).map(move |y| (self.ids.id(x, y), self.pixel_position(x, y, defaults)))
+        })
+    }
+}
+
+/// A grid of quadrilateral pixels in the xy-plane of the detector whose corners are given by `vertex` tags. \
+/// The vertices are listed row by row with x changing fastest, so there are `(xpixels + 1) * (ypixels + 1)` of them.
+/// # Example
+/// ```
+/// use mantid_idf::{defaults::Defaults, detectors::StructuredDetector, structs::{Component, Type}, Point};
+/// use nalgebra::Point2;
+///
+/// let mut type_ = Type::default();
+///
+/// for (key, value) in [("xpixels", "1"), ("ypixels", "1"), ("type", "pixel")] {
+///     type_.other_attributes.insert(key.to_string(), value.to_string());
+/// }
+///
+/// type_.vertices = vec![Point2::new(0.0, 0.0), Point2::new(2.0, 0.0), Point2::new(0.0, 1.0), Point2::new(2.0, 1.0)];
+///
+/// let detector = StructuredDetector::from_type_and_component(&type_, &Component::default()).unwrap();
+///
+/// let pixel = detector.pixels(&Defaults::default()).next().unwrap();
+///
+/// assert_eq!(pixel.centre, Point::new(1.0, 0.5, 0.0));
+/// assert_eq!(pixel.corners[2], Point::new(2.0, 1.0, 0.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructuredDetector {
+    /// The number of pixels along x.
+    pub xpixels: usize,
+    /// The number of pixels along y.
+    pub ypixels: usize,
+    /// The type of the pixels.
+    pub pixel_type: String,
+    /// The corners of the pixels, see `Type::vertices`.
+    pub vertices: Vec<Point2<f32>>,
+    /// The numbering of the pixels.
+    pub ids: PixelIDs,
+}
+
+/// A pixel of a `StructuredDetector` in the coordinates of the detector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructuredPixel {
+    /// The id of the pixel.
+    pub id: i64,
+    /// The mean of the corners.
+    pub centre: Point,
+    /// The corners of the pixel, counterclockwise starting at the lowest x and y.
+    pub corners: [Point; 4],
+}
+
+impl StructuredDetector {
+    /// Reads the geometry from the attributes and vertices of the type and the ids from the attributes of the component. \
+    /// Errors if a required attribute is missing or the number of vertices does not match the number of pixels.
+    pub fn from_type_and_component(type_: &Type, component: &Component) -> anyhow::Result<Self> {
+        let attributes = &type_.other_attributes;
+
+        let xpixels = required_attribute(attributes, "xpixels", &type_.name)?;
+        let ypixels = required_attribute(attributes, "ypixels", &type_.name)?;
+
+        let expected_vertices = (xpixels + 1) * (ypixels + 1);
+
+        if type_.vertices.len() != expected_vertices {
+            return Err(anyhow::anyhow!(
+                "structured detector {} has {} vertices, but {} are needed for {}x{} pixels",
+                type_.name,
+                type_.vertices.len(),
+                expected_vertices,
+                xpixels,
+                ypixels
+            ));
+        }
+
+        Ok(Self {
+            xpixels,
+            ypixels,
+            pixel_type: required_attribute(attributes, "type", &type_.name)?,
+            vertices: type_.vertices.clone(),
+            ids: PixelIDs::from_component(component, xpixels, ypixels)?,
+        })
+    }
+
+    /// The vertex with the index `x` along x and `y` along y in metres, in the coordinates of the detector.
+    pub fn vertex(&self, x: usize, y: usize, defaults: &Defaults) -> Point {
+        let vertex = self.vertices[y * (self.xpixels + 1) + x];
+
+        Point::new(
+            defaults.length_to_metres(vertex.x),
+            defaults.length_to_metres(vertex.y),
+            0.0,
+        )
+    }
+
+    /// The pixel with the index `x` along x and `y` along y.
+    pub fn pixel(&self, x: usize, y: usize, defaults: &Defaults) -> StructuredPixel {
+        let corners = [
+            self.vertex(x, y, defaults),
+            self.vertex(x + 1, y, defaults),
+            self.vertex(x + 1, y + 1, defaults),
+            self.vertex(x, y + 1, defaults),
+        ];
+
+        let centre = Point::from(
+            corners
+                .iter()
+                .map(|corner| corner.coords)
+                .sum::<Vector3<f32>>()
+                / 4.0,
+        );
+
+        StructuredPixel {
+            id: self.ids.id(x, y),
+            centre,
+            corners,
+        }
+    }
+
+    /// All pixels of the detector. x is the outer and y the inner index, like for rectangular detectors.
+    pub fn pixels<'a>(
+        &'a self,
+        defaults: &'a Defaults,
+    ) -> impl Iterator<Item = StructuredPixel> + 'a {
+        (0..self.xpixels)
+            .flat_map(move |x| (0..self.ypixels).map(move |y| self.pixel(x, y, defaults)))
+    }
+}
+
+/// A three dimensional grid of voxels. \
+/// The geometry is defined by the attributes of the `GridDetector` type,
+/// the ids by the attributes of the component that uses the type.
+/// # Example
+/// ```
+/// use mantid_idf::{defaults::Defaults, detectors::GridDetector, structs::{Component, Type}, Point};
+///
+/// let mut type_ = Type::default();
+/// let mut component = Component::default();
+///
+/// for axis in ["x", "y", "z"] {
+///     type_.other_attributes.insert(format!("{}pixels", axis), "2".to_string());
+///     type_.other_attributes.insert(format!("{}start", axis), "0".to_string());
+///     type_.other_attributes.insert(format!("{}step", axis), "0.5".to_string());
+/// }
+///
+/// type_.other_attributes.insert("type".to_string(), "voxel".to_string());
+/// component.other_attributes.insert("idfillorder".to_string(), "zyx".to_string());
+///
+/// let detector = GridDetector::from_type_and_component(&type_, &component).unwrap();
+///
+/// // The ids are filled along z first, then y and then x
+/// assert_eq!(detector.ids.id([0, 0, 1]), 1);
+/// assert_eq!(detector.ids.id([0, 1, 0]), 2);
+/// assert_eq!(detector.ids.id([1, 0, 0]), 4);
+/// assert_eq!(detector.voxel_position([1, 0, 1], &Defaults::default()), Point::new(0.5, 0.0, 0.5));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridDetector {
+    /// The number of voxels along x, y and z.
+    pub pixels: [usize; 3],
+    /// The coordinates of the first voxel.
+    pub start: [f32; 3],
+    /// The distances between two voxels along x, y and z.
+    pub step: [f32; 3],
+    /// The type of the voxels. Its shape is the shape of every voxel.
+    pub pixel_type: String,
+    /// The numbering of the voxels.
+    pub ids: GridIDs,
+}
+
+/// The numbering of the voxels of a `GridDetector`. \
+/// Defined by the `idstart`, `idfillorder`, `idstep`, `idstepbyrow` and `idstepbylayer` attributes of the component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridIDs {
+    /// The id of the first voxel. Defaults to 0.
+    pub idstart: i64,
+    /// The order in which the axes are filled, e.g. `xyz` fills along x first and along z last. Defaults to `xyz`.
+    pub idfillorder: [Axes; 3],
+    /// The difference between the ids of two neighbouring voxels along the first axis. Defaults to 1.
+    pub idstep: i64,
+    /// The difference between the ids of two neighbouring rows along the second axis.
+    /// Defaults to the number of voxels along the first axis.
+    pub idstepbyrow: i64,
+    /// The difference between the ids of two neighbouring layers along the third axis.
+    /// Defaults to the number of voxels in one layer.
+    pub idstepbylayer: i64,
+}
+
+impl GridIDs {
+    /// Reads the id attributes of the component of a grid detector with the given number of voxels along x, y and z.
+    pub fn from_component(component: &Component, pixels: [usize; 3]) -> anyhow::Result<Self> {
+        let attributes = &component.other_attributes;
+
+        let idfillorder = match attributes.get("idfillorder") {
+            Some(idfillorder) => parse_fill_order(idfillorder).context(format!(
+                "could not parse idfillorder of component {}",
+                component.type_name
+            ))?,
+            None => [Axes::X, Axes::Y, Axes::Z],
+        };
+
+        let first = pixels[axis_index(idfillorder[0])] as i64;
+        let second = pixels[axis_index(idfillorder[1])] as i64;
+
+        let idstepbyrow =
+            optional_attribute(attributes, "idstepbyrow", &component.type_name)?.unwrap_or(first);
+
+        Ok(Self {
+            idstart: optional_attribute(attributes, "idstart", &component.type_name)?.unwrap_or(0),
+            idfillorder,
+            idstep: optional_attribute(attributes, "idstep", &component.type_name)?.unwrap_or(1),
+            idstepbyrow,
+            idstepbylayer: optional_attribute(attributes, "idstepbylayer", &component.type_name)?
+                .unwrap_or(idstepbyrow * second),
+        })
+    }
+
+    /// The id of the voxel with the indices `[x, y, z]`.
+    pub fn id(&self, index: [usize; 3]) -> i64 {
+        let [first, second, third] = self.idfillorder.map(|axis| index[axis_index(axis)] as i64);
+
+        self.idstart + first * self.idstep + second * self.idstepbyrow + third * self.idstepbylayer
+    }
+}
+
+impl GridDetector {
+    /// Reads the geometry from the attributes of the type and the ids from the attributes of the component. \
+    /// Errors if a required attribute is missing or can not be parsed.
+    pub fn from_type_and_component(type_: &Type, component: &Component) -> anyhow::Result<Self> {
+        let attributes = &type_.other_attributes;
+
+        let mut pixels = [0; 3];
+        let mut start = [0.0; 3];
+        let mut step = [0.0; 3];
+
+        for (index, axis) in ["x", "y", "z"].iter().enumerate() {
+            pixels[index] =
+                required_attribute(attributes, &format!("{}pixels", axis), &type_.name)?;
+            start[index] = required_attribute(attributes, &format!("{}start", axis), &type_.name)?;
+            step[index] = required_attribute(attributes, &format!("{}step", axis), &type_.name)?;
+        }
+
+        Ok(Self {
+            pixels,
+            start,
+            step,
+            pixel_type: required_attribute(attributes, "type", &type_.name)?,
+            ids: GridIDs::from_component(component, pixels)?,
+        })
+    }
+
+    /// The centre of the voxel with the indices `[x, y, z]` in metres, in the coordinates of the detector.
+    pub fn voxel_position(&self, index: [usize; 3], defaults: &Defaults) -> Point {
+        Point::from([0, 1, 2].map(|axis| {
+            defaults.length_to_metres(self.start[axis] + index[axis] as f32 * self.step[axis])
+        }))
+    }
+
+    /// The ids and positions of all voxels. x is the outermost and z the innermost index.
+    pub fn voxels<'a>(&'a self, defaults: &'a Defaults) -> impl Iterator<Item = (i64, Point)> + 'a {
+        let [xpixels, ypixels, zpixels] = self.pixels;
+
+        (0..xpixels).flat_map(move |x| {
+            (0..ypixels).flat_map(move |y| {
+                (0..zpixels).map(move |z| {
+                    (
+                        self.ids.id([x, y, z]),
+                        self.voxel_position([x, y, z], defaults),
+                    )
+                })
+            })
+        })
+    }
+}
+
+fn axis_index(axis: Axes) -> usize {
+    match axis {
+        Axes::X => 0,
+        Axes::Y => 1,
+        Axes::Z => 2,
+    }
+}
+
+/// Parses a permutation of `xyz`, e.g. `zyx`.
+fn parse_fill_order(s: &str) -> anyhow::Result<[Axes; 3]> {
+    let axes = s
+        .trim()
+        .chars()
+        .map(|char| char.to_string().parse::<Axes>())
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let fill_order: [Axes; 3] = axes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("{:?} does not contain exactly three axes", s))?;
+
+    if fill_order
+        .iter()
+        .enumerate()
+        .any(|(index, axis)| fill_order[..index].contains(axis))
+    {
+        return Err(anyhow::anyhow!("{:?} contains an axis twice", s));
+    }
+
+    Ok(fill_order)
+}
+
+fn optional_attribute<T>(
+    attributes: &BTreeMap<String, String>,
+    name: &str,
+    owner: &str,
+) -> anyhow::Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: Into<anyhow::Error>,
+{
+    attributes
+        .get(name)
+        .map(|value| {
+            value
+                .trim()
+                .parse::<T>()
+                .map_err(Into::<anyhow::Error>::into)
+                .context(format!(
+                    "could not parse attribute {} = {:?} of {}",
+                    name, value, owner
+                ))
+        })
+        .transpose()
+}
+
+fn required_attribute<T>(
+    attributes: &BTreeMap<String, String>,
+    name: &str,
+    owner: &str,
+) -> anyhow::Result<T>
+where
+    T: FromStr,
+    T::Err: Into<anyhow::Error>,
+{
+    optional_attribute(attributes, name, owner)?
+        .context(format!("could not find attribute {} of {}", name, owner))
+}
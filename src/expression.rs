@@ -0,0 +1,414 @@
+//! A small evaluator for the mathematical expressions used in the `eq` attribute of `logfile` and `formula` tags. \
+//! Supports numbers, variables, `+ - * / ^`, parentheses and the common functions of muParser (which is used by Mantid). \
+//! https://docs.mantidproject.org/nightly/concepts/InstrumentDefinitionFile.html#using-logfile
+
+use anyhow::Context;
+
+/// A parsed mathematical expression.
+/// # Example
+/// ```
+/// use mantid_idf::expression::Expression;
+///
+/// let expression: Expression = "2*value+1".parse().unwrap();
+///
+/// assert_eq!(expression.evaluate(|name| (name == "value").then_some(3.0)).unwrap(), 7.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    /// A constant number.
+    Number(f64),
+    /// A variable which is looked up during the evaluation.
+    Variable(String),
+    /// Negation of the inner expression.
+    Negate(Box<Expression>),
+    /// A binary operation.
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+    /// A function call, e.g. `sin(x)`.
+    Function(String, Vec<Expression>),
+}
+
+/// The binary operators of an `Expression`.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+impl Expression {
+    /// Evaluates the expression. \
+    /// `variables` is called for every variable in the expression and has to return its value. \
+    /// The constants `pi`/`_pi` and `e`/`_e` are always available.
+    pub fn evaluate<F>(&self, variables: F) -> anyhow::Result<f64>
+    where
+        F: Fn(&str) -> Option<f64> + Copy,
+    {
+        match self {
+            Self::Number(number) => Ok(*number),
+            Self::Variable(name) => match variables(name) {
+                Some(value) => Ok(value),
+                None => match name.as_str() {
+                    "pi" | "_pi" => Ok(std::f64::consts::PI),
+                    "e" | "_e" => Ok(std::f64::consts::E),
+                    _ => Err(anyhow::anyhow!("unknown variable {:?} in expression", name)),
+                },
+            },
+            Self::Negate(inner) => Ok(-inner.evaluate(variables)?),
+            Self::Binary(operator, left, right) => {
+                let left = left.evaluate(variables)?;
+                let right = right.evaluate(variables)?;
+
+                Ok(match operator {
+                    BinaryOperator::Add => left + right,
+                    BinaryOperator::Subtract => left - right,
+                    BinaryOperator::Multiply => left * right,
+                    BinaryOperator::Divide => left / right,
+                    BinaryOperator::Power => left.powf(right),
+                })
+            }
+            Self::Function(name, arguments) => {
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| argument.evaluate(variables))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+
+                evaluate_function(name, &arguments)
+            }
+        }
+    }
+}
+
+fn evaluate_function(name: &str, arguments: &[f64]) -> anyhow::Result<f64> {
+    let value = match (name, arguments) {
+        ("sin", [x]) => x.sin(),
+        ("cos", [x]) => x.cos(),
+        ("tan", [x]) => x.tan(),
+        ("asin", [x]) => x.asin(),
+        ("acos", [x]) => x.acos(),
+        ("atan", [x]) => x.atan(),
+        ("sinh", [x]) => x.sinh(),
+        ("cosh", [x]) => x.cosh(),
+        ("tanh", [x]) => x.tanh(),
+        ("sqrt", [x]) => x.sqrt(),
+        ("exp", [x]) => x.exp(),
+        ("ln", [x]) => x.ln(),
+        ("log", [x]) => x.ln(),
+        ("log10", [x]) => x.log10(),
+        ("log2", [x]) => x.log2(),
+        ("abs", [x]) => x.abs(),
+        ("sign", [x]) => x.signum(),
+        ("rint", [x]) => x.round(),
+        ("atan2", [y, x]) => y.atan2(*x),
+        ("pow", [x, y]) => x.powf(*y),
+        ("min", [first, rest @ ..]) => rest.iter().fold(*first, |min, x| min.min(*x)),
+        ("max", [first, rest @ ..]) => rest.iter().fold(*first, |max, x| max.max(*x)),
+        ("sum", arguments) => arguments.iter().sum(),
+        ("avg", arguments) if !arguments.is_empty() => {
+            arguments.iter().sum::<f64>() / arguments.len() as f64
+        }
+        _ => {
+            return Err(anyhow::anyhow!(
+                "unknown function {:?} with {} arguments in expression",
+                name,
+                arguments.len()
+            ))
+        }
+    };
+
+    Ok(value)
+}
+
+impl std::str::FromStr for Expression {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s).context(format!("could not tokenize expression {:?}", s))?;
+
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+        };
+
+        let expression = parser
+            .parse_expression()
+            .context(format!("could not parse expression {:?}", s))?;
+
+        match parser.peek() {
+            None => Ok(expression),
+            Some(token) => Err(anyhow::anyhow!(
+                "unexpected token {:?} in expression {:?}",
+                token,
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    Operator(char),
+    OpenParenthesis,
+    CloseParenthesis,
+    Comma,
+}
+
+fn tokenize(s: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+
+    let mut chars = s.char_indices().peekable();
+
+    while let Some(&(start, char)) = chars.peek() {
+        match char {
+            char if char.is_whitespace() => {
+                chars.next();
+            }
+            '0'..='9' | '.' => {
+                let mut end = start;
+
+                while let Some(&(index, char)) = chars.peek() {
+                    let is_exponent_sign = matches!(char, '+' | '-')
+                        && matches!(s[..index].chars().last(), Some('e' | 'E'));
+
+                    if char.is_ascii_digit() || matches!(char, '.' | 'e' | 'E') || is_exponent_sign
+                    {
+                        end = index + char.len_utf8();
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+
+                let number = &s[start..end];
+
+                tokens.push(Token::Number(
+                    number
+                        .parse()
+                        .context(format!("could not parse number {:?}", number))?,
+                ));
+            }
+            char if char.is_alphabetic() || char == '_' => {
+                let mut end = start;
+
+                while let Some(&(index, char)) = chars.peek() {
+                    if char.is_alphanumeric() || char == '_' {
+                        end = index + char.len_utf8();
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+
+                tokens.push(Token::Identifier(s[start..end].to_string()));
+            }
+            '+' | '-' | '*' | '/' | '^' => {
+                tokens.push(Token::Operator(char));
+                chars.next();
+            }
+            '(' => {
+                tokens.push(Token::OpenParenthesis);
+                chars.next();
+            }
+            ')' => {
+                tokens.push(Token::CloseParenthesis);
+                chars.next();
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                chars.next();
+            }
+            _ => return Err(anyhow::anyhow!("unexpected character {:?}", char)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser. \
+/// `^` binds stronger than unary minus (`-2^2 == -4`) and is right associative.
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.position);
+
+        self.position += 1;
+
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> anyhow::Result<()> {
+        match self.next() {
+            Some(token) if *token == expected => Ok(()),
+            token => Err(anyhow::anyhow!(
+                "expected {:?}, found {:?}",
+                expected,
+                token
+            )),
+        }
+    }
+
+    fn parse_expression(&mut self) -> anyhow::Result<Expression> {
+        let mut left = self.parse_term()?;
+
+        while let Some(Token::Operator(operator @ ('+' | '-'))) = self.peek() {
+            let operator = match operator {
+                '+' => BinaryOperator::Add,
+                _ => BinaryOperator::Subtract,
+            };
+
+            self.next();
+
+            let right = self.parse_term()?;
+
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn parse_term(&mut self) -> anyhow::Result<Expression> {
+        let mut left = self.parse_unary()?;
+
+        while let Some(Token::Operator(operator @ ('*' | '/'))) = self.peek() {
+            let operator = match operator {
+                '*' => BinaryOperator::Multiply,
+                _ => BinaryOperator::Divide,
+            };
+
+            self.next();
+
+            let right = self.parse_unary()?;
+
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> anyhow::Result<Expression> {
+        match self.peek() {
+            Some(Token::Operator('-')) => {
+                self.next();
+
+                Ok(Expression::Negate(Box::new(self.parse_unary()?)))
+            }
+            Some(Token::Operator('+')) => {
+                self.next();
+
+                self.parse_unary()
+            }
+            _ => self.parse_power(),
+        }
+    }
+
+    fn parse_power(&mut self) -> anyhow::Result<Expression> {
+        let base = self.parse_primary()?;
+
+        match self.peek() {
+            Some(Token::Operator('^')) => {
+                self.next();
+
+                let exponent = self.parse_unary()?;
+
+                Ok(Expression::Binary(
+                    BinaryOperator::Power,
+                    Box::new(base),
+                    Box::new(exponent),
+                ))
+            }
+            _ => Ok(base),
+        }
+    }
+
+    fn parse_primary(&mut self) -> anyhow::Result<Expression> {
+        match self.next().cloned() {
+            Some(Token::Number(number)) => Ok(Expression::Number(number)),
+            Some(Token::Identifier(name)) => {
+                if self.peek() != Some(&Token::OpenParenthesis) {
+                    return Ok(Expression::Variable(name));
+                }
+
+                self.next();
+
+                let mut arguments = Vec::new();
+
+                if self.peek() != Some(&Token::CloseParenthesis) {
+                    arguments.push(self.parse_expression()?);
+
+                    while self.peek() == Some(&Token::Comma) {
+                        self.next();
+
+                        arguments.push(self.parse_expression()?);
+                    }
+                }
+
+                self.expect(Token::CloseParenthesis)?;
+
+                Ok(Expression::Function(name, arguments))
+            }
+            Some(Token::OpenParenthesis) => {
+                let expression = self.parse_expression()?;
+
+                self.expect(Token::CloseParenthesis)?;
+
+                Ok(expression)
+            }
+            token => Err(anyhow::anyhow!("unexpected token {:?}", token)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_expression {
+    use super::*;
+
+    fn evaluate(expression: &str, value: f64) -> f64 {
+        expression
+            .parse::<Expression>()
+            .unwrap()
+            .evaluate(|name| (name == "value").then_some(value))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_operator_precedence() {
+        assert_eq!(evaluate("2*value+1", 3.0), 7.0);
+        assert_eq!(evaluate("1+2*3-4/2", 0.0), 5.0);
+        assert_eq!(evaluate("(1+2)*3", 0.0), 9.0);
+        assert_eq!(evaluate("2^3^2", 0.0), 512.0);
+        assert_eq!(evaluate("-2^2", 0.0), -4.0);
+        assert_eq!(evaluate("-value*2", 1.5), -3.0);
+        assert_eq!(evaluate("1.5e-3*1e3", 0.0), 1.5);
+    }
+
+    #[test]
+    fn test_functions_and_constants() {
+        assert!((evaluate("sin(pi/2)", 0.0) - 1.0).abs() < 1e-12);
+        assert_eq!(evaluate("max(1, value, 3)", 5.0), 5.0);
+        assert_eq!(evaluate("sqrt(abs(value))", -16.0), 4.0);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!("2*".parse::<Expression>().is_err());
+        assert!("(1+2".parse::<Expression>().is_err());
+        assert!("1 2".parse::<Expression>().is_err());
+        assert!("unknown+1"
+            .parse::<Expression>()
+            .unwrap()
+            .evaluate(|_| None)
+            .is_err());
+    }
+}
@@ -2,11 +2,28 @@
 //! The ids of the components can be defined in different ways. \
 //! A component can have the `idlist` attribute, which references an `idlist` by name. \
 //! Any components that are children of a component with an `idlist` attribute will get the id assigned by `idlist`. \
-//! This means that the ids generated by the `idlist` have to match the number of children of the component. \
-//! TODO: Enforce the above rule or give a warning if it is not followed.
+//! This means that the ids generated by the `idlist` have to match the number of detectors below the component. \
+//! The ids are assigned during the expansion of the component tree, see `ComponentTreeNode::get_detectors`. \
+//! Mismatches can be listed with `ComponentTree::validate_id_lists`.
+
+use std::fmt::Display;
 
 /// IDList is one way to define the ids of the different components.
-#[derive(Debug, Default)]
+/// # Example
+/// ```
+/// use mantid_idf::idlists::{IDEntry, IDList};
+///
+/// let id_list = IDList {
+///     name: "ids".to_string(),
+///     entries: vec![IDEntry::new(1, 9, 4).unwrap(), IDEntry::single(20)],
+/// };
+///
+/// assert_eq!(id_list.len(), 4);
+/// assert!(id_list.contains(5));
+/// assert!(!id_list.contains(6));
+/// assert_eq!(id_list.get_ids(), [1, 5, 9, 20]);
+/// ```
+#[derive(Debug, Default, Clone)]
 pub struct IDList {
     /// The name of the IDList. Has to be referenced in some component as `idlist` to be used.
     pub name: String,
@@ -16,18 +33,163 @@ pub struct IDList {
 
 impl IDList {
     /// Converts the IDList to a list of IDs.
-    pub fn get_ids(&self) -> Vec<usize> {
-        self.entries
-            .iter()
-            .flat_map(|entry| entry.start..=entry.end)
-            .collect()
+    pub fn get_ids(&self) -> Vec<i64> {
+        self.iter().collect()
+    }
+
+    /// Iterates over the ids of all entries in order, without collecting them.
+    pub fn iter(&self) -> impl Iterator<Item = i64> + '_ {
+        self.entries.iter().flat_map(IDEntry::iter)
+    }
+
+    /// The number of ids of the IDList.
+    pub fn len(&self) -> usize {
+        self.entries.iter().map(IDEntry::len).sum()
+    }
+
+    /// Check if the IDList contains no ids.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Check if any entry of the IDList contains the id.
+    pub fn contains(&self, id: i64) -> bool {
+        self.entries.iter().any(|entry| entry.contains(id))
     }
 }
 
-#[allow(missing_docs)]
-#[derive(Debug, Default)]
-/// An entry in the IDList. It defines a range of IDs.
+/// An entry in the IDList. It defines a range of IDs. \
+/// Represents either `<id start="1" end="9" step="2"/>` or `<id val="5"/>`. \
+/// Like in Mantid, ids and steps can be negative.
+/// # Example
+/// ```
+/// use mantid_idf::idlists::IDEntry;
+///
+/// // The range ends at the last id that does not pass `end`
+/// let entry = IDEntry::new(1, 100, 2).unwrap();
+///
+/// assert_eq!(entry.len(), 50);
+/// assert_eq!(entry.iter().last(), Some(99));
+/// assert!(!entry.contains(100));
+///
+/// // A negative step counts down from `start` to `end`
+/// let entry = IDEntry::new(-1, -7, -3).unwrap();
+///
+/// assert_eq!(entry.iter().collect::<Vec<_>>(), [-1, -4, -7]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct IDEntry {
-    pub start: usize,
-    pub end: usize,
+    /// The first id of the range.
+    pub start: i64,
+    /// The bound of the range (inclusive), the last id is the last one that does not pass it.
+    pub end: i64,
+    /// The distance between two ids of the range, negative to count down from `start`.
+    pub step: i64,
+}
+
+impl Default for IDEntry {
+    fn default() -> Self {
+        Self::single(0)
+    }
+}
+
+impl IDEntry {
+    /// Creates a range of ids. \
+    /// Errors if `step` is zero or leads away from `end`.
+    pub fn new(start: i64, end: i64, step: i64) -> anyhow::Result<Self> {
+        let entry = Self { start, end, step };
+
+        entry.validate()?;
+
+        Ok(entry)
+    }
+
+    /// Creates an entry with a single id.
+    pub fn single(id: i64) -> Self {
+        Self {
+            start: id,
+            end: id,
+            step: 1,
+        }
+    }
+
+    /// Checks the rules Mantid enforces for the `id` tag.
+    pub(crate) fn validate(&self) -> anyhow::Result<()> {
+        if self.step == 0 {
+            return Err(anyhow::anyhow!(
+                "step of id entry {:?} must not be zero",
+                self
+            ));
+        }
+
+        if self.step > 0 && self.end < self.start {
+            return Err(anyhow::anyhow!(
+                "end of id entry {:?} must not be smaller than start for a positive step",
+                self
+            ));
+        }
+
+        if self.step < 0 && self.end > self.start {
+            return Err(anyhow::anyhow!(
+                "end of id entry {:?} must not be larger than start for a negative step",
+                self
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Iterates over the ids of the entry.
+    pub fn iter(&self) -> impl Iterator<Item = i64> {
+        let Self { start, step, .. } = *self;
+
+        (0..self.len() as i64).map(move |index| start + index * step)
+    }
+
+    /// The number of ids of the entry. An invalid entry has no ids.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        if self.validate().is_err() {
+            return 0;
+        }
+
+        ((self.end - self.start) / self.step) as usize + 1
+    }
+
+    /// Check if the id is part of the entry.
+    pub fn contains(&self, id: i64) -> bool {
+        self.step != 0
+            && (id - self.start) % self.step == 0
+            && (0..self.len() as i64).contains(&((id - self.start) / self.step))
+    }
+}
+
+/// A mismatch between an `idlist` and the detectors of the component that references it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IDListMismatch {
+    /// The name of the `idlist`.
+    pub idlist: String,
+    /// The type of the component that references the `idlist`.
+    pub component: String,
+    /// The number of ids of the `idlist`, `None` if there is no `idlist` with that name.
+    pub ids: Option<usize>,
+    /// The number of detectors below the component that do not get their id from elsewhere.
+    pub detectors: usize,
+}
+
+impl Display for IDListMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.ids {
+            Some(ids) => write!(
+                f,
+                "idlist {} has {} ids, but component {} has {} detectors",
+                self.idlist, ids, self.component, self.detectors
+            ),
+            None => write!(
+                f,
+                "could not find idlist {} of component {}",
+                self.idlist, self.component
+            ),
+        }
+    }
 }
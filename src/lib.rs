@@ -5,8 +5,14 @@
 //! https://docs.mantidproject.org/nightly/concepts/InstrumentDefinitionFile.html
 
 pub mod component_tree;
+pub mod defaults;
 pub mod detector_definition;
+pub mod detectors;
+pub mod expression;
 pub mod idlists;
+pub mod parameter_file;
+pub mod parameters;
+pub mod sample_logs;
 pub mod shapes;
 pub mod structs;
 pub mod types;
@@ -0,0 +1,56 @@
+//! Module for Mantid parameter files (`*_Parameters.xml`). \
+//! A parameter file contains `component-link` tags which attach parameters to components of an instrument by name or path. \
+//! https://docs.mantidproject.org/nightly/concepts/InstrumentParameterFile.html
+
+use anyhow::Context;
+
+use crate::{parameters::Parameters, xml_parser::parameter_file_from_str};
+
+/// Represents the `parameter-file` tag.
+#[derive(Debug, Default, Clone)]
+pub struct ParameterFile {
+    /// The name of the instrument the parameter file belongs to.
+    pub instrument: String,
+    /// The date from which the parameter file is valid.
+    pub valid_from: Option<String>,
+    /// The component links of the parameter file.
+    pub component_links: Vec<ComponentLink>,
+}
+
+impl ParameterFile {
+    /// Parse the parameter file from a string.
+    /// # Example
+    /// ```
+    /// use mantid_idf::parameter_file::ParameterFile;
+    ///
+    /// let content = r#"
+    ///     <parameter-file instrument="Test">
+    ///         <component-link name="VoxelsRoot">
+    ///             <parameter name="TubePressure">
+    ///                 <value val="10.0"/>
+    ///             </parameter>
+    ///         </component-link>
+    ///     </parameter-file>
+    /// "#;
+    ///
+    /// let parameter_file = ParameterFile::from_str(content).expect("could not parse parameter file");
+    ///
+    /// assert_eq!(parameter_file.instrument, "Test");
+    /// assert_eq!(parameter_file.component_links[0].name, "VoxelsRoot");
+    /// ```
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(str: &str) -> anyhow::Result<Self> {
+        parameter_file_from_str(str).context("could not parse parameter file")
+    }
+}
+
+/// Represents the `component-link` tag. \
+/// Links the parameters to all components that match the name. \
+/// The name can be a single component name or a path of names separated by `/`, e.g. `bank1/tube3`.
+#[derive(Debug, Default, Clone)]
+pub struct ComponentLink {
+    /// The name or path of the linked components.
+    pub name: String,
+    /// The parameters that are attached to the linked components.
+    pub parameters: Parameters,
+}
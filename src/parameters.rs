@@ -0,0 +1,445 @@
+//! This module contains the `Parameter` struct which represents the `parameter` tag. \
+//! Parameters can be attached to components and types of the instrument definition file. \
+//! https://docs.mantidproject.org/nightly/concepts/InstrumentDefinitionFile.html#using-parameter
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use anyhow::Context;
+
+use crate::{expression::Expression, sample_logs::SampleLogs};
+
+/// Parameters by their name.
+pub type Parameters = BTreeMap<String, Parameter>;
+
+/// The parameters of a component (including the parameters of its type) and a link to the parameters of its parent. \
+/// Used to look up parameters through the parent chain of a `ComponentTreeNode`.
+#[derive(Debug, Default)]
+pub(crate) struct ParameterScope {
+    pub parameters: Parameters,
+    pub parent: Option<Arc<ParameterScope>>,
+}
+
+impl ParameterScope {
+    /// Searches the scope and all its parents for the parameter.
+    pub fn find(&self, name: &str) -> Option<&Parameter> {
+        match self.parameters.get(name) {
+            Some(parameter) => Some(parameter),
+            None => self.parent.as_ref()?.find(name),
+        }
+    }
+
+    /// Collects the parameters of the scope and all its parents. Closer scopes take precedence.
+    pub fn collect_into(&self, parameters: &mut Parameters) {
+        if let Some(parent) = self.parent.as_ref() {
+            parent.collect_into(parameters);
+        }
+
+        parameters.extend(self.parameters.clone());
+    }
+}
+
+/// Represents the `parameter` tag in the IDF file.
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    /// The name of the parameter.
+    pub name: String,
+    /// The type of the parameter. Represents the `type` attribute.
+    pub parameter_type: ParameterType,
+    /// The value of the parameter. `None` if the parameter did not define a value.
+    pub value: Option<ParameterValue>,
+    /// The unit of the parameter if specified.
+    pub unit: Option<String>,
+    /// The description of the parameter. Represents the `description` tag.
+    pub description: Option<String>,
+    /// Whether the parameter is visible in Mantid. Represents the `visible` attribute.
+    pub visible: bool,
+}
+
+impl Default for Parameter {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            parameter_type: ParameterType::default(),
+            value: None,
+            unit: None,
+            description: None,
+            visible: true,
+        }
+    }
+}
+
+impl Parameter {
+    /// Returns the value of the parameter converted to `T`.
+    /// # Example
+    /// ```
+    /// use mantid_idf::parameters::{Parameter, ParameterValue};
+    ///
+    /// let parameter = Parameter {
+    ///     name: "TubePressure".to_string(),
+    ///     value: Some(ParameterValue::Value("10.0".to_string())),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(parameter.get_value::<f64>().unwrap(), 10.0);
+    /// ```
+    pub fn get_value<T: FromParameter>(&self) -> anyhow::Result<T> {
+        T::from_parameter(self).context(format!("could not get value of parameter {}", self.name))
+    }
+
+    pub(crate) fn raw_value(&self) -> anyhow::Result<&str> {
+        match self.value.as_ref() {
+            Some(ParameterValue::Value(value)) => Ok(value),
+            Some(_) => Err(anyhow::anyhow!(
+                "parameter {} has no fixed value, it has to be resolved",
+                self.name
+            )),
+            None => Err(anyhow::anyhow!("parameter {} has no value", self.name)),
+        }
+    }
+
+    /// Resolves the numeric value of the parameter. \
+    /// Fixed values are parsed, `logfile` values are extracted from the sample logs and evaluated with `eq`. \
+    /// Returns `Ok(None)` if the parameter has no value, is a `formula` or `lookuptable` or its sample log is missing
+    /// (Mantid ignores such parameters).
+    /// # Example
+    /// ```
+    /// use mantid_idf::{
+    ///     parameters::{ExtractSingleValueAs, LogFile, Parameter, ParameterValue},
+    ///     sample_logs::{SampleLogs, TimeSeries},
+    /// };
+    ///
+    /// let parameter = Parameter {
+    ///     name: "x".to_string(),
+    ///     value: Some(ParameterValue::LogFile(LogFile {
+    ///         id: "motor".to_string(),
+    ///         eq: Some("2*value+1".parse().unwrap()),
+    ///         extract_single_value_as: ExtractSingleValueAs::LastValue,
+    ///     })),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let sample_logs = SampleLogs::from([("motor".to_string(), TimeSeries::from_values(vec![1.0, 3.0]))]);
+    ///
+    /// assert_eq!(parameter.resolve(&sample_logs).unwrap(), Some(7.0));
+    /// assert_eq!(parameter.resolve(&SampleLogs::new()).unwrap(), None);
+    /// ```
+    pub fn resolve(&self, sample_logs: &SampleLogs) -> anyhow::Result<Option<f64>> {
+        match self.value.as_ref() {
+            Some(ParameterValue::Value(_)) => self.get_value().map(Some),
+            Some(ParameterValue::LogFile(log_file)) => log_file
+                .resolve(sample_logs)
+                .context(format!("could not resolve parameter {}", self.name)),
+            Some(ParameterValue::Formula(_) | ParameterValue::LookupTable(_)) | None => Ok(None),
+        }
+    }
+}
+
+/// The type of the parameter. Mantid uses `double` if no type is specified.
+#[allow(missing_docs)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterType {
+    #[default]
+    Double,
+    Int,
+    Bool,
+    String,
+    /// A parameter that is used as a starting value (and constraint) for fitting.
+    Fitting,
+}
+
+impl std::str::FromStr for ParameterType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "double" | "number" => Ok(Self::Double),
+            "int" => Ok(Self::Int),
+            "bool" => Ok(Self::Bool),
+            "string" => Ok(Self::String),
+            "fitting" => Ok(Self::Fitting),
+            _ => Err(anyhow::anyhow!(
+                "could not parse string: {:?} to ParameterType",
+                s
+            )),
+        }
+    }
+}
+
+/// The different ways the value of a parameter can be defined.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParameterValue {
+    /// A fixed value. Represents the `value` tag.
+    Value(String),
+    /// A value taken from a sample log. Represents the `logfile` tag.
+    LogFile(LogFile),
+    /// A value that depends on another variable. Represents the `formula` tag.
+    Formula(Formula),
+    /// A table of values that is interpolated. Represents the `lookuptable` tag.
+    LookupTable(LookupTable),
+}
+
+/// Represents the `logfile` tag. \
+/// The value of the parameter is extracted from the sample log `id` of a run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFile {
+    /// The name of the sample log.
+    pub id: String,
+    /// Optional expression that is applied to the extracted value, which is available as `value`.
+    pub eq: Option<Expression>,
+    /// How the time series of the sample log is reduced to a single value.
+    pub extract_single_value_as: ExtractSingleValueAs,
+}
+
+impl LogFile {
+    /// Extracts the value from the sample logs and applies `eq`. \
+    /// Returns `Ok(None)` if the sample log is missing or has no value.
+    pub fn resolve(&self, sample_logs: &SampleLogs) -> anyhow::Result<Option<f64>> {
+        let Some(value) = sample_logs
+            .get(&self.id)
+            .and_then(|time_series| time_series.extract_single_value(self.extract_single_value_as))
+        else {
+            return Ok(None);
+        };
+
+        match self.eq.as_ref() {
+            Some(eq) => eq
+                .evaluate(|name| (name == "value").then_some(value))
+                .map(Some),
+            None => Ok(Some(value)),
+        }
+    }
+}
+
+/// How a time series is reduced to a single value. \
+/// Represents the `extract-single-value-as` attribute. Mantid uses `mean` if not specified.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ExtractSingleValueAs {
+    /// The time-weighted mean of all values.
+    #[default]
+    Mean,
+    /// The median of all values.
+    Median,
+    /// The first value.
+    FirstValue,
+    /// The last value.
+    LastValue,
+    /// The smallest value.
+    Minimum,
+    /// The largest value.
+    Maximum,
+    /// The value at the (zero-based) position, e.g. `position 1`.
+    Position(usize),
+}
+
+impl std::str::FromStr for ExtractSingleValueAs {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "mean" | "average" => Ok(Self::Mean),
+            "median" => Ok(Self::Median),
+            "first_value" => Ok(Self::FirstValue),
+            "last_value" => Ok(Self::LastValue),
+            "minimum" => Ok(Self::Minimum),
+            "maximum" => Ok(Self::Maximum),
+            s => match s.strip_prefix("position").map(str::trim) {
+                Some(position) => Ok(Self::Position(
+                    position
+                        .parse()
+                        .context(format!("could not parse position of string: {:?}", s))?,
+                )),
+                None => Err(anyhow::anyhow!(
+                    "could not parse string: {:?} to ExtractSingleValueAs",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+/// Represents the `formula` tag. \
+/// Mantid uses formulas for fitting parameters whose starting value depends on the centre of the peak,
+/// which is available as `centre` in `eq`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    /// The expression of the formula.
+    pub eq: Expression,
+    /// The unit of the variable (`centre`).
+    pub unit: Option<String>,
+    /// The unit of the result.
+    pub result_unit: Option<String>,
+}
+
+impl Formula {
+    /// Evaluates the formula for the given centre.
+    /// # Example
+    /// ```
+    /// use mantid_idf::parameters::Formula;
+    ///
+    /// let formula = Formula { eq: "100.0+10*centre+centre^2".parse().unwrap(), unit: None, result_unit: None };
+    ///
+    /// assert_eq!(formula.evaluate(2.0).unwrap(), 124.0);
+    /// ```
+    pub fn evaluate(&self, centre: f64) -> anyhow::Result<f64> {
+        self.eq
+            .evaluate(|name| (name == "centre").then_some(centre))
+    }
+}
+
+/// Represents the `lookuptable` tag. \
+/// A table of `point`s (`x`, `y`) that is interpolated to get the value for any `x`. \
+/// The points are expected to be sorted by `x`, like Mantid requires.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LookupTable {
+    /// How the values between the points are interpolated.
+    pub interpolation: Interpolation,
+    /// The unit of the `x` values. Represents the `x-unit` attribute.
+    pub x_unit: Option<String>,
+    /// The unit of the `y` values. Represents the `y-unit` attribute.
+    pub y_unit: Option<String>,
+    /// The points of the table as (`x`, `y`).
+    pub points: Vec<(f64, f64)>,
+}
+
+impl LookupTable {
+    /// Returns the interpolated value at `x`. Returns `None` if the table has no points. \
+    /// Outside of the table the linear interpolation is extrapolated from the first or last two points,
+    /// the histogram interpolation uses the first or last value.
+    /// # Example
+    /// ```
+    /// use mantid_idf::parameters::{Interpolation, LookupTable};
+    ///
+    /// let mut lookup_table = LookupTable {
+    ///     points: vec![(0.0, 1.0), (10.0, 2.0), (20.0, 4.0)],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(lookup_table.value(5.0), Some(1.5));
+    /// assert_eq!(lookup_table.value(30.0), Some(6.0));
+    ///
+    /// lookup_table.interpolation = Interpolation::Histogram;
+    ///
+    /// assert_eq!(lookup_table.value(15.0), Some(2.0));
+    /// ```
+    pub fn value(&self, x: f64) -> Option<f64> {
+        let (first, last) = (self.points.first()?, self.points.last()?);
+
+        if self.points.len() == 1 {
+            return Some(first.1);
+        }
+
+        match self.interpolation {
+            Interpolation::Linear => {
+                // Index of the first point of the segment that contains `x`
+                let index = self
+                    .points
+                    .partition_point(|(point_x, _)| *point_x <= x)
+                    .clamp(1, self.points.len() - 1)
+                    - 1;
+
+                let (x0, y0) = self.points[index];
+                let (x1, y1) = self.points[index + 1];
+
+                match x1 == x0 {
+                    true => Some(y0),
+                    false => Some(y0 + (x - x0) * (y1 - y0) / (x1 - x0)),
+                }
+            }
+            Interpolation::Histogram => {
+                if x < first.0 {
+                    return Some(first.1);
+                }
+
+                if x >= last.0 {
+                    return Some(last.1);
+                }
+
+                let index = self.points.partition_point(|(point_x, _)| *point_x <= x) - 1;
+
+                Some(self.points[index].1)
+            }
+        }
+    }
+}
+
+/// How the values of a `LookupTable` are interpolated. Mantid uses `linear` if not specified.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Linear interpolation between the points.
+    #[default]
+    Linear,
+    /// The value of a point applies until the next point.
+    Histogram,
+}
+
+impl std::str::FromStr for Interpolation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "linear" => Ok(Self::Linear),
+            "histogram" => Ok(Self::Histogram),
+            _ => Err(anyhow::anyhow!(
+                "could not parse string: {:?} to Interpolation",
+                s
+            )),
+        }
+    }
+}
+
+/// Conversion of a `Parameter` to a concrete type.
+pub trait FromParameter: Sized {
+    /// Converts the value of the parameter.
+    fn from_parameter(parameter: &Parameter) -> anyhow::Result<Self>;
+}
+
+macro_rules! impl_from_parameter_with_from_str {
+    ($($type_:ty),*) => {
+        $(
+            impl FromParameter for $type_ {
+                fn from_parameter(parameter: &Parameter) -> anyhow::Result<Self> {
+                    let value = parameter.raw_value()?;
+
+                    value.trim().parse::<$type_>().context(format!(
+                        "could not parse string: {:?} to {:?}",
+                        value,
+                        std::any::type_name::<$type_>()
+                    ))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_parameter_with_from_str!(f32, f64, i32, i64, u32, u64, usize);
+
+impl FromParameter for bool {
+    fn from_parameter(parameter: &Parameter) -> anyhow::Result<Self> {
+        match parameter.raw_value()?.trim() {
+            "true" | "True" | "TRUE" | "1" => Ok(true),
+            "false" | "False" | "FALSE" | "0" => Ok(false),
+            value => Err(anyhow::anyhow!(
+                "could not parse string: {:?} to bool",
+                value
+            )),
+        }
+    }
+}
+
+impl FromParameter for String {
+    fn from_parameter(parameter: &Parameter) -> anyhow::Result<Self> {
+        parameter.raw_value().map(str::to_string)
+    }
+}
+
+impl FromParameter for LookupTable {
+    fn from_parameter(parameter: &Parameter) -> anyhow::Result<Self> {
+        match parameter.value.as_ref() {
+            Some(ParameterValue::LookupTable(lookup_table)) => Ok(lookup_table.clone()),
+            _ => Err(anyhow::anyhow!(
+                "parameter {} is not a lookuptable",
+                parameter.name
+            )),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Module for the sample logs of a run. \
+//! Sample logs are time series (e.g. motor positions) recorded during a measurement. \
+//! They are used to resolve parameters that are defined with the `logfile` tag. \
+//! https://docs.mantidproject.org/nightly/concepts/InstrumentDefinitionFile.html#using-logfile
+
+use std::collections::BTreeMap;
+
+use crate::parameters::ExtractSingleValueAs;
+
+/// Sample logs by their name. The name corresponds to the `id` attribute of the `logfile` tag.
+pub type SampleLogs = BTreeMap<String, TimeSeries>;
+
+/// A time series of a sample log. \
+/// The entries are expected in chronological order.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TimeSeries {
+    /// The times of the entries, e.g. in seconds since the start of the run.
+    pub times: Vec<f64>,
+    /// The values of the entries.
+    pub values: Vec<f64>,
+}
+
+impl TimeSeries {
+    /// Creates a time series from values only. The times are set to the index of the value.
+    /// # Example
+    /// ```
+    /// use mantid_idf::sample_logs::TimeSeries;
+    ///
+    /// let time_series = TimeSeries::from_values(vec![1.0, 2.0]);
+    ///
+    /// assert_eq!(time_series.times, [0.0, 1.0]);
+    /// ```
+    pub fn from_values(values: Vec<f64>) -> Self {
+        Self {
+            times: (0..values.len()).map(|index| index as f64).collect(),
+            values,
+        }
+    }
+
+    /// Reduces the time series to a single value. \
+    /// The mean is weighted with the time each value was held until the next entry. \
+    /// Returns `None` if the time series is empty or the requested position does not exist.
+    /// # Example
+    /// ```
+    /// use mantid_idf::{parameters::ExtractSingleValueAs, sample_logs::TimeSeries};
+    ///
+    /// let time_series = TimeSeries::from_values(vec![3.0, 1.0, 2.0]);
+    ///
+    /// assert_eq!(time_series.extract_single_value(ExtractSingleValueAs::Mean), Some(2.0));
+    /// assert_eq!(time_series.extract_single_value(ExtractSingleValueAs::LastValue), Some(2.0));
+    /// assert_eq!(time_series.extract_single_value(ExtractSingleValueAs::Position(1)), Some(1.0));
+    ///
+    /// // The first value is held three times as long as the second one
+    /// let time_series = TimeSeries { times: vec![0.0, 3.0, 4.0], values: vec![1.0, 5.0, 100.0] };
+    ///
+    /// assert_eq!(time_series.extract_single_value(ExtractSingleValueAs::Mean), Some(2.0));
+    /// ```
+    pub fn extract_single_value(
+        &self,
+        extract_single_value_as: ExtractSingleValueAs,
+    ) -> Option<f64> {
+        if self.values.is_empty() {
+            return None;
+        }
+
+        match extract_single_value_as {
+            ExtractSingleValueAs::Mean => Some(self.time_weighted_mean()),
+            ExtractSingleValueAs::Median => {
+                let mut values = self.values.clone();
+
+                values.sort_by(f64::total_cmp);
+
+                let middle = values.len() / 2;
+
+                match values.len() % 2 {
+                    0 => Some((values[middle - 1] + values[middle]) / 2.0),
+                    _ => Some(values[middle]),
+                }
+            }
+            ExtractSingleValueAs::FirstValue => self.values.first().copied(),
+            ExtractSingleValueAs::LastValue => self.values.last().copied(),
+            ExtractSingleValueAs::Minimum => self.values.iter().copied().reduce(f64::min),
+            ExtractSingleValueAs::Maximum => self.values.iter().copied().reduce(f64::max),
+            ExtractSingleValueAs::Position(position) => self.values.get(position).copied(),
+        }
+    }
+
+    /// The mean of the values weighted with their duration, like the time averaged value of Mantid. \
+    /// Each value is held until the time of the next entry, so the last value does not contribute. \
+    /// Falls back to the plain mean if the entries do not span any time, e.g. for a single entry. \
+    /// The time series must not be empty.
+    fn time_weighted_mean(&self) -> f64 {
+        let (weighted_sum, duration) = self.times.windows(2).zip(self.values.iter()).fold(
+            (0.0, 0.0),
+            |(weighted_sum, duration), (times, value)| {
+                let held = times[1] - times[0];
+
+                (weighted_sum + value * held, duration + held)
+            },
+        );
+
+        match duration > 0.0 {
+            true => weighted_sum / duration,
+            false => self.values.iter().sum::<f64>() / self.values.len() as f64,
+        }
+    }
+}
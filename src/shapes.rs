@@ -1,7 +1,13 @@
 //! Definition for the different shapes that can be found in the IDF file.
-//! https://docs.mantidproject.org/nightly/concepts/HowToDefineGeometricShape.html
+//! https://docs.mantidproject.org/nightly/concepts/HowToDefineGeometricShape.html \
+//! Lengths are in metres and angles in degrees, independent of the `Defaults` (like Mantid does).
 
-use crate::Point;
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use nalgebra::{Isometry3, Rotation3, Vector3};
+
+use crate::{utils::spherical_to_cartesian, Point};
 
 /// Represents the different shapes that can be found in the IDF file.
 #[allow(missing_docs)]
@@ -9,6 +15,479 @@ use crate::Point;
 pub enum Shapes {
     Cuboid(Cuboid),
     Hexahedron(Hexahedron),
+    Cylinder(Cylinder),
+    HollowCylinder(HollowCylinder),
+    Sphere(Sphere),
+    Cone(Cone),
+    InfiniteCylinder(InfiniteCylinder),
+    InfinitePlane(InfinitePlane),
+    InfiniteCone(InfiniteCone),
+    SliceOfCylinderRing(SliceOfCylinderRing),
+    Torus(Torus),
+    TaperedGuide(TaperedGuide),
+    /// The union (`:`) of shapes.
+    Union(Vec<Shapes>),
+    /// The intersection (juxtaposition) of shapes.
+    Intersection(Vec<Shapes>),
+    /// The complement (`#`) of a shape.
+    Complement(Box<Shapes>),
+}
+
+impl Shapes {
+    /// The tags of the shapes that can be parsed.
+    pub(crate) const TAGS: &'static [&'static str] = &[
+        "cuboid",
+        "hexahedron",
+        "cylinder",
+        "hollow-cylinder",
+        "sphere",
+        "cone",
+        "infinite-cylinder",
+        "infinite-plane",
+        "infinite-cone",
+        "slice-of-cylinder-ring",
+        "torus",
+        "tapered-guide",
+    ];
+
+    /// The id of the shape. Combined shapes have no id.
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Cuboid(cuboid) => &cuboid.id,
+            Self::Hexahedron(hexahedron) => &hexahedron.id,
+            Self::Cylinder(cylinder) => &cylinder.id,
+            Self::HollowCylinder(hollow_cylinder) => &hollow_cylinder.id,
+            Self::Sphere(sphere) => &sphere.id,
+            Self::Cone(cone) => &cone.id,
+            Self::InfiniteCylinder(infinite_cylinder) => &infinite_cylinder.id,
+            Self::InfinitePlane(infinite_plane) => &infinite_plane.id,
+            Self::InfiniteCone(infinite_cone) => &infinite_cone.id,
+            Self::SliceOfCylinderRing(slice_of_cylinder_ring) => &slice_of_cylinder_ring.id,
+            Self::Torus(torus) => &torus.id,
+            Self::TaperedGuide(tapered_guide) => &tapered_guide.id,
+            Self::Union(_) | Self::Intersection(_) | Self::Complement(_) => "",
+        }
+    }
+
+    /// Combines the shapes of a type according to the `algebra` tag. \
+    /// `:` is the union, juxtaposition (whitespace) the intersection and `#` the complement of the following term,
+    /// e.g. `(a : b) # c`. The intersection binds stronger than the union. \
+    /// Without an algebra the shapes are intersected. A single shape is returned as it is.
+    /// # Example
+    /// ```
+    /// use mantid_idf::shapes::{Shapes, Sphere};
+    ///
+    /// let sphere = |id: &str| Shapes::Sphere(Sphere { id: id.to_string(), centre: Default::default(), radius: 1.0 });
+    ///
+    /// let shape = Shapes::from_algebra(vec![sphere("a"), sphere("b"), sphere("c")], Some("(a : b) #c")).unwrap();
+    ///
+    /// let Some(Shapes::Intersection(shapes)) = shape else { panic!() };
+    ///
+    /// assert!(matches!(shapes[0], Shapes::Union(_)));
+    /// assert!(matches!(shapes[1], Shapes::Complement(_)));
+    /// ```
+    pub fn from_algebra(
+        shapes: Vec<Shapes>,
+        algebra: Option<&str>,
+    ) -> anyhow::Result<Option<Shapes>> {
+        let named_shapes = shapes
+            .into_iter()
+            .map(|shape| (shape.id().to_string(), shape))
+            .collect();
+
+        Self::from_named_algebra(named_shapes, algebra)
+    }
+
+    /// Like `from_algebra`, but the algebra refers to the shapes by the given names instead of their ids. \
+    /// Without an algebra the shapes are intersected, like Mantid does for a type without an `algebra` tag.
+    pub(crate) fn from_named_algebra(
+        named_shapes: Vec<(String, Shapes)>,
+        algebra: Option<&str>,
+    ) -> anyhow::Result<Option<Shapes>> {
+        let Some(algebra) = algebra.filter(|algebra| !algebra.trim().is_empty()) else {
+            return Ok(Self::intersection(
+                named_shapes.into_iter().map(|(_, shape)| shape).collect(),
+            ));
+        };
+
+        let tokens = tokenize_algebra(algebra);
+
+        let mut parser = AlgebraParser {
+            shapes: &named_shapes,
+            tokens: &tokens,
+            position: 0,
+        };
+
+        let shape = parser
+            .parse_union()
+            .context(format!("could not parse algebra {:?}", algebra))?;
+
+        match parser.tokens.get(parser.position) {
+            None => Ok(Some(shape)),
+            Some(token) => Err(anyhow::anyhow!(
+                "unexpected {:?} in algebra {:?}",
+                token,
+                algebra
+            )),
+        }
+    }
+
+    /// Returns the shape moved by the isometry, i.e. all points are transformed and all axes rotated. \
+    /// Shapes without points or axes (`slice-of-cylinder-ring`) keep the transformation instead.
+    /// # Example
+    /// ```
+    /// use mantid_idf::{shapes::{Shapes, Sphere}, Point};
+    ///
+    /// let sphere = Shapes::Sphere(Sphere { id: "a".to_string(), centre: Point::origin(), radius: 1.0 });
+    ///
+    /// let moved = sphere.transformed(&nalgebra::Isometry3::translation(1.0, 0.0, 0.0)).unwrap();
+    ///
+    /// assert!(matches!(moved, Shapes::Sphere(sphere) if sphere.centre == Point::new(1.0, 0.0, 0.0)));
+    /// ```
+    pub fn transformed(&self, isometry: &Isometry3<f32>) -> anyhow::Result<Shapes> {
+        let point = |point: &Point| isometry.transform_point(point);
+        let axis = |axis: &Vector3<f32>| isometry.transform_vector(axis);
+
+        let shape = match self {
+            Self::Cuboid(cuboid) => Self::Cuboid(Cuboid {
+                left_front_bottom_point: point(&cuboid.left_front_bottom_point),
+                left_front_top_point: point(&cuboid.left_front_top_point),
+                left_back_bottom_point: point(&cuboid.left_back_bottom_point),
+                right_front_bottom_point: point(&cuboid.right_front_bottom_point),
+                centre: point(&cuboid.centre),
+                ..cuboid.clone()
+            }),
+            Self::Hexahedron(hexahedron) => Self::Hexahedron(Hexahedron {
+                id: hexahedron.id.clone(),
+                left_back_bottom_point: point(&hexahedron.left_back_bottom_point),
+                left_front_bottom_point: point(&hexahedron.left_front_bottom_point),
+                right_front_bottom_point: point(&hexahedron.right_front_bottom_point),
+                right_back_bottom_point: point(&hexahedron.right_back_bottom_point),
+                left_back_top_point: point(&hexahedron.left_back_top_point),
+                left_front_top_point: point(&hexahedron.left_front_top_point),
+                right_front_top_point: point(&hexahedron.right_front_top_point),
+                right_back_top_point: point(&hexahedron.right_back_top_point),
+            }),
+            Self::Cylinder(cylinder) => Self::Cylinder(Cylinder {
+                centre_of_bottom_base: point(&cylinder.centre_of_bottom_base),
+                axis: axis(&cylinder.axis),
+                ..cylinder.clone()
+            }),
+            Self::HollowCylinder(hollow_cylinder) => Self::HollowCylinder(HollowCylinder {
+                centre_of_bottom_base: point(&hollow_cylinder.centre_of_bottom_base),
+                axis: axis(&hollow_cylinder.axis),
+                ..hollow_cylinder.clone()
+            }),
+            Self::Sphere(sphere) => Self::Sphere(Sphere {
+                centre: point(&sphere.centre),
+                ..sphere.clone()
+            }),
+            Self::Cone(cone) => Self::Cone(Cone {
+                tip_point: point(&cone.tip_point),
+                axis: axis(&cone.axis),
+                ..cone.clone()
+            }),
+            Self::InfiniteCylinder(infinite_cylinder) => Self::InfiniteCylinder(InfiniteCylinder {
+                centre: point(&infinite_cylinder.centre),
+                axis: axis(&infinite_cylinder.axis),
+                ..infinite_cylinder.clone()
+            }),
+            Self::InfinitePlane(infinite_plane) => Self::InfinitePlane(InfinitePlane {
+                point_in_plane: point(&infinite_plane.point_in_plane),
+                normal_to_plane: axis(&infinite_plane.normal_to_plane),
+                ..infinite_plane.clone()
+            }),
+            Self::InfiniteCone(infinite_cone) => Self::InfiniteCone(InfiniteCone {
+                tip_point: point(&infinite_cone.tip_point),
+                axis: axis(&infinite_cone.axis),
+                ..infinite_cone.clone()
+            }),
+            Self::Torus(torus) => Self::Torus(Torus {
+                centre: point(&torus.centre),
+                axis: axis(&torus.axis),
+                ..torus.clone()
+            }),
+            Self::TaperedGuide(tapered_guide) => Self::TaperedGuide(TaperedGuide {
+                centre: point(&tapered_guide.centre),
+                axis: axis(&tapered_guide.axis),
+                width_axis: axis(&tapered_guide.width_axis),
+                ..tapered_guide.clone()
+            }),
+            Self::SliceOfCylinderRing(slice_of_cylinder_ring) => {
+                Self::SliceOfCylinderRing(SliceOfCylinderRing {
+                    transformation: isometry * slice_of_cylinder_ring.transformation,
+                    ..slice_of_cylinder_ring.clone()
+                })
+            }
+            Self::Union(shapes) => Self::Union(
+                shapes
+                    .iter()
+                    .map(|shape| shape.transformed(isometry))
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+            Self::Intersection(shapes) => Self::Intersection(
+                shapes
+                    .iter()
+                    .map(|shape| shape.transformed(isometry))
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+            Self::Complement(shape) => Self::Complement(Box::new(shape.transformed(isometry)?)),
+        };
+
+        Ok(shape)
+    }
+
+    fn union(mut shapes: Vec<Shapes>) -> Option<Shapes> {
+        match shapes.len() {
+            0 => None,
+            1 => shapes.pop(),
+            _ => Some(Self::Union(shapes)),
+        }
+    }
+
+    fn intersection(mut shapes: Vec<Shapes>) -> Option<Shapes> {
+        match shapes.len() {
+            0 => None,
+            1 => shapes.pop(),
+            _ => Some(Self::Intersection(shapes)),
+        }
+    }
+}
+
+fn tokenize_algebra(algebra: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut identifier = String::new();
+
+    for char in algebra.chars() {
+        if char.is_whitespace() || matches!(char, ':' | '#' | '(' | ')') {
+            if !identifier.is_empty() {
+                tokens.push(std::mem::take(&mut identifier));
+            }
+
+            if !char.is_whitespace() {
+                tokens.push(char.to_string());
+            }
+        } else {
+            identifier.push(char);
+        }
+    }
+
+    if !identifier.is_empty() {
+        tokens.push(identifier);
+    }
+
+    tokens
+}
+
+/// Recursive descent parser for the `algebra` of shapes.
+struct AlgebraParser<'a> {
+    shapes: &'a [(String, Shapes)],
+    tokens: &'a [String],
+    position: usize,
+}
+
+impl AlgebraParser<'_> {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(String::as_str)
+    }
+
+    fn parse_union(&mut self) -> anyhow::Result<Shapes> {
+        let mut shapes = vec![self.parse_intersection()?];
+
+        while self.peek() == Some(":") {
+            self.position += 1;
+
+            shapes.push(self.parse_intersection()?);
+        }
+
+        Ok(Shapes::union(shapes).expect("union has at least one shape"))
+    }
+
+    fn parse_intersection(&mut self) -> anyhow::Result<Shapes> {
+        let mut shapes = vec![self.parse_complement()?];
+
+        while self
+            .peek()
+            .is_some_and(|token| token != ":" && token != ")")
+        {
+            shapes.push(self.parse_complement()?);
+        }
+
+        Ok(Shapes::intersection(shapes).expect("intersection has at least one shape"))
+    }
+
+    fn parse_complement(&mut self) -> anyhow::Result<Shapes> {
+        match self.peek() {
+            Some("#") => {
+                self.position += 1;
+
+                Ok(Shapes::Complement(Box::new(self.parse_complement()?)))
+            }
+            _ => self.parse_primary(),
+        }
+    }
+
+    fn parse_primary(&mut self) -> anyhow::Result<Shapes> {
+        let token = self
+            .peek()
+            .context("unexpected end of algebra")?
+            .to_string();
+
+        self.position += 1;
+
+        match token.as_str() {
+            "(" => {
+                let shape = self.parse_union()?;
+
+                match self.peek() {
+                    Some(")") => {
+                        self.position += 1;
+
+                        Ok(shape)
+                    }
+                    token => Err(anyhow::anyhow!("expected \")\", found {:?}", token)),
+                }
+            }
+            ":" | ")" => Err(anyhow::anyhow!("unexpected {:?}", token)),
+            name => self
+                .shapes
+                .iter()
+                .find(|(shape_name, _)| shape_name == name)
+                .map(|(_, shape)| shape.clone())
+                .context(format!("could not find shape {:?}", name)),
+        }
+    }
+}
+
+/// Collects the id and the child elements of a shape tag while parsing. \
+/// The child elements are stored by their tag with their raw attributes, e.g. `<radius val="0.1"/>`.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ShapeBuilder {
+    pub tag: String,
+    pub id: String,
+    pub elements: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl ShapeBuilder {
+    /// Builds the shape from the collected elements.
+    pub fn build(&self) -> anyhow::Result<Shapes> {
+        let shape = match self.tag.as_str() {
+            "cuboid" => Shapes::Cuboid(Cuboid::from_builder(self)?),
+            "hexahedron" => Shapes::Hexahedron(Hexahedron::from_builder(self)?),
+            "cylinder" => Shapes::Cylinder(Cylinder::from_builder(self)?),
+            "hollow-cylinder" => Shapes::HollowCylinder(HollowCylinder::from_builder(self)?),
+            "sphere" => Shapes::Sphere(Sphere::from_builder(self)?),
+            "cone" => Shapes::Cone(Cone::from_builder(self)?),
+            "infinite-cylinder" => Shapes::InfiniteCylinder(InfiniteCylinder::from_builder(self)?),
+            "infinite-plane" => Shapes::InfinitePlane(InfinitePlane::from_builder(self)?),
+            "infinite-cone" => Shapes::InfiniteCone(InfiniteCone::from_builder(self)?),
+            "slice-of-cylinder-ring" => {
+                Shapes::SliceOfCylinderRing(SliceOfCylinderRing::from_builder(self)?)
+            }
+            "torus" => Shapes::Torus(Torus::from_builder(self)?),
+            "tapered-guide" => Shapes::TaperedGuide(TaperedGuide::from_builder(self)?),
+            tag => return Err(anyhow::anyhow!("unknown shape {:?}", tag)),
+        };
+
+        Ok(shape)
+    }
+
+    fn has(&self, element: &str) -> bool {
+        self.elements.contains_key(element)
+    }
+
+    fn attribute<T>(&self, element: &str, attribute: &str) -> anyhow::Result<Option<T>>
+    where
+        T: std::str::FromStr,
+        <T as std::str::FromStr>::Err: Into<anyhow::Error>,
+    {
+        let Some(value) = self
+            .elements
+            .get(element)
+            .and_then(|attributes| attributes.get(attribute))
+        else {
+            return Ok(None);
+        };
+
+        value
+            .parse::<T>()
+            .map(Some)
+            .map_err(Into::<anyhow::Error>::into)
+            .context(format!(
+                "could not parse attribute {:?} of {:?} of shape {}",
+                attribute, element, self.id
+            ))
+    }
+
+    fn required_attribute(&self, element: &str, attribute: &str) -> anyhow::Result<f32> {
+        self.attribute(element, attribute)?.context(format!(
+            "missing attribute {:?} of {:?} of shape {} ({})",
+            attribute, element, self.id, self.tag
+        ))
+    }
+
+    /// The `val` attribute of the element.
+    fn value(&self, element: &str) -> anyhow::Result<f32> {
+        self.required_attribute(element, "val")
+    }
+
+    /// A point given by `x`, `y`, `z` or by `r`, `t`, `p` (in degrees). Missing coordinates are zero.
+    fn optional_point(&self, element: &str) -> anyhow::Result<Option<Point>> {
+        let Some(attributes) = self.elements.get(element) else {
+            return Ok(None);
+        };
+
+        let coordinate = |key: &str| {
+            self.attribute::<f32>(element, key)
+                .map(Option::unwrap_or_default)
+        };
+
+        let point = match ["r", "t", "p"]
+            .iter()
+            .any(|key| attributes.contains_key(*key))
+        {
+            true => spherical_to_cartesian(Point::new(
+                coordinate("r")?,
+                coordinate("t")?.to_radians(),
+                coordinate("p")?.to_radians(),
+            )),
+            false => Point::new(coordinate("x")?, coordinate("y")?, coordinate("z")?),
+        };
+
+        Ok(Some(point))
+    }
+
+    fn point(&self, element: &str) -> anyhow::Result<Point> {
+        self.optional_point(element)?.context(format!(
+            "missing {:?} of shape {} ({})",
+            element, self.id, self.tag
+        ))
+    }
+
+    /// A direction given like a point. Errors if the direction has zero length.
+    fn axis(&self, element: &str) -> anyhow::Result<Vector3<f32>> {
+        let axis = self.point(element)?.coords;
+
+        if axis.norm() == 0.0 {
+            return Err(anyhow::anyhow!(
+                "{:?} of shape {} ({}) has zero length",
+                element,
+                self.id,
+                self.tag
+            ));
+        }
+
+        Ok(axis)
+    }
+}
+
+/// The rotation that maps the z-axis onto the axis. \
+/// Mantid defines shapes like cuboids and cylinders along the z-axis and rotates them onto their `axis`.
+fn rotation_from_z_to(axis: Vector3<f32>) -> Rotation3<f32> {
+    Rotation3::rotation_between(&Vector3::z(), &axis).unwrap_or_else(|| {
+        // The axis is anti-parallel to the z-axis
+        Rotation3::from_axis_angle(&Vector3::x_axis(), std::f32::consts::PI)
+    })
 }
 
 /// Represents a hexahedron in the IDF file.
@@ -27,22 +506,24 @@ pub struct Hexahedron {
 }
 
 impl Hexahedron {
-    pub(crate) fn get_mut(&mut self, key: &[u8]) -> Option<&mut Point> {
-        match key {
-            b"left-back-bottom-point" => Some(&mut self.left_back_bottom_point),
-            b"left-front-bottom-point" => Some(&mut self.left_front_bottom_point),
-            b"right-front-bottom-point" => Some(&mut self.right_front_bottom_point),
-            b"right-back-bottom-point" => Some(&mut self.right_back_bottom_point),
-            b"left-back-top-point" => Some(&mut self.left_back_top_point),
-            b"left-front-top-point" => Some(&mut self.left_front_top_point),
-            b"right-front-top-point" => Some(&mut self.right_front_top_point),
-            b"right-back-top-point" => Some(&mut self.right_back_top_point),
-            _ => None,
-        }
+    fn from_builder(builder: &ShapeBuilder) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: builder.id.clone(),
+            left_back_bottom_point: builder.point("left-back-bottom-point")?,
+            left_front_bottom_point: builder.point("left-front-bottom-point")?,
+            right_front_bottom_point: builder.point("right-front-bottom-point")?,
+            right_back_bottom_point: builder.point("right-back-bottom-point")?,
+            left_back_top_point: builder.point("left-back-top-point")?,
+            left_front_top_point: builder.point("left-front-top-point")?,
+            right_front_top_point: builder.point("right-front-top-point")?,
+            right_back_top_point: builder.point("right-back-top-point")?,
+        })
     }
 }
 
-/// Represents a cuboid in the IDF file.
+/// Represents a cuboid in the IDF file. \
+/// Can be defined by four corner points or by `width` (x), `height` (y), `depth` (z), `centre` and `axis`. \
+/// The `axis` is the direction of the depth, the cuboid is rotated from the z-axis onto it.
 #[allow(missing_docs)]
 #[derive(Debug, Default, Clone)]
 pub struct Cuboid {
@@ -69,11 +550,12 @@ impl Cuboid {
         let width = nalgebra::distance(&left_front_bottom_point, &right_front_bottom_point);
         let height = nalgebra::distance(&left_front_bottom_point, &left_front_top_point);
         let depth = nalgebra::distance(&left_front_bottom_point, &left_back_bottom_point);
-        let centre = Point::new(
-            (left_front_bottom_point.x + right_front_bottom_point.x) / 2.0,
-            (left_front_bottom_point.y + left_front_top_point.y) / 2.0,
-            (left_front_bottom_point.z + left_back_bottom_point.z) / 2.0,
-        );
+        // The centre is half way along all three edges, which also holds for rotated cuboids
+        let centre = left_front_bottom_point
+            + ((right_front_bottom_point - left_front_bottom_point)
+                + (left_front_top_point - left_front_bottom_point)
+                + (left_back_bottom_point - left_front_bottom_point))
+                / 2.0;
 
         Self {
             id,
@@ -88,41 +570,455 @@ impl Cuboid {
         }
     }
 
-    /// Create a new cuboid from the given dimensions.
+    /// Create a new cuboid from the given dimensions. \
+    /// The width is along the x-axis, the height along the y-axis and the depth along the z-axis.
     pub fn from_dimensions(id: String, centre: Point, width: f32, height: f32, depth: f32) -> Self {
-        let left_front_bottom_point = Point::new(
-            centre.x - width / 2.0,
-            centre.y - height / 2.0,
-            centre.z - depth / 2.0,
-        );
-        let left_front_top_point = Point::new(
-            centre.x - width / 2.0,
-            centre.y + height / 2.0,
-            centre.z - depth / 2.0,
-        );
-        let left_back_bottom_point = Point::new(
-            centre.x - width / 2.0,
-            centre.y - height / 2.0,
-            centre.z + depth / 2.0,
-        );
-        let right_front_bottom_point = Point::new(
-            centre.x + width / 2.0,
-            centre.y - height / 2.0,
-            centre.z - depth / 2.0,
-        );
+        Self::from_dimensions_and_axis(id, centre, width, height, depth, Vector3::z())
+    }
+
+    /// Create a new cuboid from the given dimensions, rotated so that the depth is along `axis`.
+    /// # Example
+    /// ```
+    /// use mantid_idf::{shapes::Cuboid, Point};
+    ///
+    /// let cuboid = Cuboid::from_dimensions_and_axis(
+    ///     "cuboid".to_string(),
+    ///     Point::origin(),
+    ///     1.0,
+    ///     2.0,
+    ///     4.0,
+    ///     nalgebra::Vector3::x(),
+    /// );
+    ///
+    /// assert!((cuboid.left_back_bottom_point.x - 2.0).abs() < 1e-6);
+    /// ```
+    pub fn from_dimensions_and_axis(
+        id: String,
+        centre: Point,
+        width: f32,
+        height: f32,
+        depth: f32,
+        axis: Vector3<f32>,
+    ) -> Self {
+        let rotation = rotation_from_z_to(axis);
+
+        let corner = |x: f32, y: f32, z: f32| {
+            centre + rotation * Vector3::new(x * width, y * height, z * depth) / 2.0
+        };
 
         Self {
             id,
-            left_front_bottom_point,
-            left_front_top_point,
-            left_back_bottom_point,
-            right_front_bottom_point,
+            left_front_bottom_point: corner(-1.0, -1.0, -1.0),
+            left_front_top_point: corner(-1.0, 1.0, -1.0),
+            left_back_bottom_point: corner(-1.0, -1.0, 1.0),
+            right_front_bottom_point: corner(1.0, -1.0, -1.0),
             width,
             height,
             depth,
             centre,
         }
     }
+
+    fn from_builder(builder: &ShapeBuilder) -> anyhow::Result<Self> {
+        let id = builder.id.clone();
+
+        if builder.has("left-front-bottom-point") {
+            return Ok(Self::from_points(
+                id,
+                builder.point("left-front-bottom-point")?,
+                builder.point("left-front-top-point")?,
+                builder.point("left-back-bottom-point")?,
+                builder.point("right-front-bottom-point")?,
+            ));
+        }
+
+        Ok(Self::from_dimensions_and_axis(
+            id,
+            builder.optional_point("centre")?.unwrap_or_default(),
+            builder.value("width")?,
+            builder.value("height")?,
+            builder.value("depth")?,
+            builder
+                .optional_point("axis")?
+                .map(|axis| axis.coords)
+                .unwrap_or(Vector3::z()),
+        ))
+    }
+}
+
+/// Represents a cylinder in the IDF file. \
+/// The cylinder extends from the centre of its bottom base along the `axis` by `height`.
+#[allow(missing_docs)]
+#[derive(Debug, Clone)]
+pub struct Cylinder {
+    pub id: String,
+    pub centre_of_bottom_base: Point,
+    pub axis: Vector3<f32>,
+    pub radius: f32,
+    pub height: f32,
+}
+
+impl Cylinder {
+    fn from_builder(builder: &ShapeBuilder) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: builder.id.clone(),
+            centre_of_bottom_base: builder.point("centre-of-bottom-base")?,
+            axis: builder.axis("axis")?,
+            radius: builder.value("radius")?,
+            height: builder.value("height")?,
+        })
+    }
+
+    /// The centre of the top base of the cylinder.
+    pub fn centre_of_top_base(&self) -> Point {
+        self.centre_of_bottom_base + self.axis.normalize() * self.height
+    }
+}
+
+/// Represents a hollow cylinder (a tube) in the IDF file. \
+/// Like a `Cylinder` with the inner cylinder removed.
+#[allow(missing_docs)]
+#[derive(Debug, Clone)]
+pub struct HollowCylinder {
+    pub id: String,
+    pub centre_of_bottom_base: Point,
+    pub axis: Vector3<f32>,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub height: f32,
+}
+
+impl HollowCylinder {
+    fn from_builder(builder: &ShapeBuilder) -> anyhow::Result<Self> {
+        let hollow_cylinder = Self {
+            id: builder.id.clone(),
+            centre_of_bottom_base: builder.point("centre-of-bottom-base")?,
+            axis: builder.axis("axis")?,
+            inner_radius: builder.value("inner-radius")?,
+            outer_radius: builder.value("outer-radius")?,
+            height: builder.value("height")?,
+        };
+
+        if hollow_cylinder.inner_radius > hollow_cylinder.outer_radius {
+            return Err(anyhow::anyhow!(
+                "inner-radius of hollow-cylinder {} is larger than its outer-radius",
+                hollow_cylinder.id
+            ));
+        }
+
+        Ok(hollow_cylinder)
+    }
+}
+
+/// Represents a sphere in the IDF file.
+#[allow(missing_docs)]
+#[derive(Debug, Clone)]
+pub struct Sphere {
+    pub id: String,
+    pub centre: Point,
+    pub radius: f32,
+}
+
+impl Sphere {
+    fn from_builder(builder: &ShapeBuilder) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: builder.id.clone(),
+            // Mantid uses the origin if no centre is given
+            centre: builder.optional_point("centre")?.unwrap_or_default(),
+            radius: builder.value("radius")?,
+        })
+    }
+}
+
+/// Represents a cone in the IDF file. \
+/// The cone opens from its tip along the `axis` with the half opening `angle` (in degrees) and ends after `height`.
+#[allow(missing_docs)]
+#[derive(Debug, Clone)]
+pub struct Cone {
+    pub id: String,
+    pub tip_point: Point,
+    pub axis: Vector3<f32>,
+    pub angle: f32,
+    pub height: f32,
+}
+
+impl Cone {
+    fn from_builder(builder: &ShapeBuilder) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: builder.id.clone(),
+            tip_point: builder.point("tip-point")?,
+            axis: builder.axis("axis")?,
+            angle: builder.value("angle")?,
+            height: builder.value("height")?,
+        })
+    }
+
+    /// The radius of the base of the cone.
+    pub fn base_radius(&self) -> f32 {
+        self.height * self.angle.to_radians().tan()
+    }
+}
+
+/// Represents an infinite cylinder in the IDF file. \
+/// Only useful in combination with other shapes, e.g. intersected with two `InfinitePlane`s.
+#[allow(missing_docs)]
+#[derive(Debug, Clone)]
+pub struct InfiniteCylinder {
+    pub id: String,
+    pub centre: Point,
+    pub axis: Vector3<f32>,
+    pub radius: f32,
+}
+
+impl InfiniteCylinder {
+    fn from_builder(builder: &ShapeBuilder) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: builder.id.clone(),
+            centre: builder.point("centre")?,
+            axis: builder.axis("axis")?,
+            radius: builder.value("radius")?,
+        })
+    }
+}
+
+/// Represents an infinite plane in the IDF file. \
+/// The plane divides the space into two halves, the `normal_to_plane` selects the half.
+#[allow(missing_docs)]
+#[derive(Debug, Clone)]
+pub struct InfinitePlane {
+    pub id: String,
+    pub point_in_plane: Point,
+    pub normal_to_plane: Vector3<f32>,
+}
+
+impl InfinitePlane {
+    fn from_builder(builder: &ShapeBuilder) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: builder.id.clone(),
+            point_in_plane: builder.point("point-in-plane")?,
+            normal_to_plane: builder.axis("normal-to-plane")?,
+        })
+    }
+}
+
+/// Represents an infinite cone in the IDF file. \
+/// The cone opens from its tip along the `axis` with the half opening `angle` (in degrees).
+#[allow(missing_docs)]
+#[derive(Debug, Clone)]
+pub struct InfiniteCone {
+    pub id: String,
+    pub tip_point: Point,
+    pub axis: Vector3<f32>,
+    pub angle: f32,
+}
+
+impl InfiniteCone {
+    fn from_builder(builder: &ShapeBuilder) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: builder.id.clone(),
+            tip_point: builder.point("tip-point")?,
+            axis: builder.axis("axis")?,
+            angle: builder.value("angle")?,
+        })
+    }
+}
+
+/// Represents a slice of a cylinder ring in the IDF file. \
+/// A ring around the z-axis between `inner_radius` and `outer_radius`, `depth` long along the z-axis
+/// and cut to the opening angle `arc` (in degrees). Used for curved detector banks.
+#[allow(missing_docs)]
+#[derive(Debug, Clone)]
+pub struct SliceOfCylinderRing {
+    pub id: String,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub depth: f32,
+    pub arc: f32,
+    /// The transformation from the coordinates the ring is defined in (around the z-axis through the origin)
+    /// to the coordinates of the shape. The identity for a parsed shape, see `Shapes::transformed`.
+    pub transformation: Isometry3<f32>,
+}
+
+impl SliceOfCylinderRing {
+    fn from_builder(builder: &ShapeBuilder) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: builder.id.clone(),
+            inner_radius: builder.value("inner-radius")?,
+            outer_radius: builder.value("outer-radius")?,
+            depth: builder.value("depth")?,
+            arc: builder.value("arc")?,
+            transformation: Isometry3::identity(),
+        })
+    }
+
+    /// The centre of the ring, the origin unless the shape was moved.
+    pub fn centre(&self) -> Point {
+        self.transformation * Point::origin()
+    }
+
+    /// The axis of the ring, the z-axis unless the shape was rotated.
+    pub fn axis(&self) -> Vector3<f32> {
+        self.transformation * Vector3::z()
+    }
+}
+
+/// Represents a torus in the IDF file. \
+/// The tube of the torus with radius `radius_tube` runs around the `axis` at `radius_from_centre_to_tube`.
+#[allow(missing_docs)]
+#[derive(Debug, Clone)]
+pub struct Torus {
+    pub id: String,
+    pub centre: Point,
+    pub axis: Vector3<f32>,
+    pub radius_from_centre_to_tube: f32,
+    pub radius_tube: f32,
+}
+
+impl Torus {
+    fn from_builder(builder: &ShapeBuilder) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: builder.id.clone(),
+            centre: builder.optional_point("centre")?.unwrap_or_default(),
+            axis: builder.axis("axis")?,
+            radius_from_centre_to_tube: builder.value("radius-from-centre-to-tube")?,
+            radius_tube: builder.value("radius-tube")?,
+        })
+    }
+}
+
+/// The width and height of an aperture of a `TaperedGuide`.
+#[allow(missing_docs)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Aperture {
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Represents a tapered guide in the IDF file. \
+/// The guide starts at `centre` with the start aperture and ends after `length` along the `axis` with the end aperture. \
+/// Mantid uses the origin as centre and the z-axis as axis if they are not specified.
+#[allow(missing_docs)]
+#[derive(Debug, Clone)]
+pub struct TaperedGuide {
+    pub id: String,
+    pub aperture_start: Aperture,
+    pub length: f32,
+    pub aperture_end: Aperture,
+    pub centre: Point,
+    pub axis: Vector3<f32>,
+    /// The direction of the width of the apertures, perpendicular to the `axis`. \
+    /// Mantid rotates the x-axis like the z-axis onto the `axis`.
+    pub width_axis: Vector3<f32>,
+}
+
+impl TaperedGuide {
+    fn from_builder(builder: &ShapeBuilder) -> anyhow::Result<Self> {
+        let aperture = |element: &str| -> anyhow::Result<Aperture> {
+            Ok(Aperture {
+                width: builder.required_attribute(element, "width")?,
+                height: builder.required_attribute(element, "height")?,
+            })
+        };
+
+        let axis = match builder.has("axis") {
+            true => builder.axis("axis")?,
+            false => Vector3::z(),
+        };
+
+        Ok(Self {
+            id: builder.id.clone(),
+            aperture_start: aperture("aperture-start")?,
+            length: builder.value("length")?,
+            aperture_end: aperture("aperture-end")?,
+            centre: builder.optional_point("centre")?.unwrap_or_default(),
+            axis,
+            width_axis: rotation_from_z_to(axis) * Vector3::x(),
+        })
+    }
+}
+
+/// Represents the `bounding-box` tag of a type. \
+/// Defines the bounding box of the shape explicitly instead of letting Mantid calculate it.
+#[allow(missing_docs)]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BoundingBox {
+    pub x_min: f32,
+    pub x_max: f32,
+    pub y_min: f32,
+    pub y_max: f32,
+    pub z_min: f32,
+    pub z_max: f32,
+}
+
+impl BoundingBox {
+    pub(crate) fn get_mut(&mut self, key: &[u8]) -> Option<&mut f32> {
+        match key {
+            b"x-min" => Some(&mut self.x_min),
+            b"x-max" => Some(&mut self.x_max),
+            b"y-min" => Some(&mut self.y_min),
+            b"y-max" => Some(&mut self.y_max),
+            b"z-min" => Some(&mut self.z_min),
+            b"z-max" => Some(&mut self.z_max),
+            _ => None,
+        }
+    }
+
+    /// The eight corners of the box.
+    pub fn corners(&self) -> [Point; 8] {
+        [
+            Point::new(self.x_min, self.y_min, self.z_min),
+            Point::new(self.x_max, self.y_min, self.z_min),
+            Point::new(self.x_min, self.y_max, self.z_min),
+            Point::new(self.x_max, self.y_max, self.z_min),
+            Point::new(self.x_min, self.y_min, self.z_max),
+            Point::new(self.x_max, self.y_min, self.z_max),
+            Point::new(self.x_min, self.y_max, self.z_max),
+            Point::new(self.x_max, self.y_max, self.z_max),
+        ]
+    }
+
+    /// Returns the axis aligned box that encloses the box moved by the isometry, like Mantid does when it
+    /// transforms the bounding box of a component.
+    /// # Example
+    /// ```
+    /// use mantid_idf::shapes::BoundingBox;
+    /// use nalgebra::{Isometry3, Vector3};
+    ///
+    /// let bounding_box = BoundingBox { x_min: 0.0, x_max: 2.0, y_min: 0.0, y_max: 1.0, z_min: 0.0, z_max: 1.0 };
+    ///
+    /// // Rotated by 90 degrees around z: x becomes y and y becomes -x
+    /// let isometry = Isometry3::new(Vector3::new(10.0, 0.0, 0.0), Vector3::z() * std::f32::consts::FRAC_PI_2);
+    ///
+    /// let moved = bounding_box.transformed(&isometry);
+    ///
+    /// assert!((moved.x_min - 9.0).abs() < 1e-6 && (moved.x_max - 10.0).abs() < 1e-6);
+    /// assert!((moved.y_min - 0.0).abs() < 1e-6 && (moved.y_max - 2.0).abs() < 1e-6);
+    /// ```
+    pub fn transformed(&self, isometry: &Isometry3<f32>) -> BoundingBox {
+        let corners = self.corners().map(|corner| isometry * corner);
+
+        let min = |axis: usize| {
+            corners
+                .iter()
+                .map(|corner| corner[axis])
+                .fold(f32::INFINITY, f32::min)
+        };
+        let max = |axis: usize| {
+            corners
+                .iter()
+                .map(|corner| corner[axis])
+                .fold(f32::NEG_INFINITY, f32::max)
+        };
+
+        BoundingBox {
+            x_min: min(0),
+            x_max: max(0),
+            y_min: min(1),
+            y_max: max(1),
+            z_min: min(2),
+            z_max: max(2),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -168,4 +1064,60 @@ mod test_cuboid {
         assert_eq!(cuboid.depth, 1.0);
         assert_eq!(cuboid.centre, Point::new(0.5, 0.5, 0.5));
     }
+
+    #[test]
+    fn test_cuboid_from_rotated_points() {
+        // Cuboid rotated by 90 degrees around the y-axis: the depth points along -x
+        let cuboid = Cuboid::from_points(
+            "cuboid".to_string(),
+            Point::new(1.0, 0.0, 0.0),
+            Point::new(1.0, 2.0, 0.0),
+            Point::new(-3.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 1.0),
+        );
+
+        assert_eq!(cuboid.width, 1.0);
+        assert_eq!(cuboid.height, 2.0);
+        assert_eq!(cuboid.depth, 4.0);
+        assert_eq!(cuboid.centre, Point::new(-1.0, 1.0, 0.5));
+    }
+
+    #[test]
+    fn test_cuboid_from_dimensions_and_axis() {
+        let cuboid = Cuboid::from_dimensions_and_axis(
+            "cuboid".to_string(),
+            Point::new(1.0, 1.0, 1.0),
+            1.0,
+            2.0,
+            4.0,
+            Vector3::y(),
+        );
+
+        // The depth is along the y-axis, the width stays along the x-axis
+        assert!(
+            (cuboid.left_back_bottom_point
+                - cuboid.left_front_bottom_point
+                - Vector3::new(0.0, 4.0, 0.0))
+            .norm()
+                < 1e-6
+        );
+        assert!(
+            (cuboid.right_front_bottom_point
+                - cuboid.left_front_bottom_point
+                - Vector3::new(1.0, 0.0, 0.0))
+            .norm()
+                < 1e-6
+        );
+
+        let cuboid = Cuboid::from_points(
+            cuboid.id,
+            cuboid.left_front_bottom_point,
+            cuboid.left_front_top_point,
+            cuboid.left_back_bottom_point,
+            cuboid.right_front_bottom_point,
+        );
+
+        assert!((cuboid.centre - Point::new(1.0, 1.0, 1.0)).norm() < 1e-6);
+        assert!((cuboid.depth - 4.0).abs() < 1e-6);
+    }
 }
@@ -3,9 +3,17 @@
 
 use std::collections::BTreeMap;
 
-use nalgebra::{Rotation3, Translation3};
+use chrono::NaiveDateTime;
+use nalgebra::{Isometry3, Point2, Rotation3, Translation3, UnitQuaternion, Vector3};
 
-use crate::{shapes::Shapes, types::SpecialTypes, utils::spherical_to_cartesian, Point};
+use crate::{
+    defaults::Defaults,
+    parameters::Parameters,
+    shapes::{BoundingBox, Shapes},
+    types::SpecialTypes,
+    utils::spherical_to_cartesian,
+    Point,
+};
 
 /// Represents the type of a component.
 /// Can contain other components.
@@ -19,10 +27,67 @@ pub struct Type {
     /// The components that are part of this type.
     pub components: Vec<Component>,
     /// The shape that the type has. \
+    /// If the type defines multiple shapes they are combined according to its `algebra` tag.
     pub shape: Option<Shapes>,
     /// Other attributes that the type can have. \
     /// "Catch-all" for attributes to avoid many `Option` fields. TODO: Better solution?
     pub other_attributes: BTreeMap<String, String>,
+    /// The parameters defined inside of the type. Apply to every component of this type.
+    pub parameters: Parameters,
+    /// How the shapes of the type are combined. Represents the `algebra` tag.
+    pub algebra: Option<String>,
+    /// The bounding box of the shape if it is given explicitly. Represents the `bounding-box` tag.
+    pub bounding_box: Option<BoundingBox>,
+    /// If `true` the shapes of the components of the type are combined into the shape of the type
+    /// and the components are removed. The `algebra` refers to the names of the component locations. \
+    /// Represents the `combine-components-into-one-shape` tag.
+    pub combine_components_into_one_shape: bool,
+    /// The location in the combined shape that becomes the origin of the type. \
+    /// Represents the `translate-rotate-combined-shape-to` tag.
+    pub translate_rotate_combined_shape_to: Option<Location>,
+    /// The corners of the pixels of a structured detector, row by row with x changing fastest. \
+    /// Represents the `vertex` tags.
+    pub vertices: Vec<Point2<f32>>,
+}
+
+/// Represents the attributes of the `instrument` tag, the root of the IDF file.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Instrument {
+    /// The name of the instrument. Component links with this name refer to the whole instrument.
+    pub name: String,
+    /// The start of the period in which the definition is valid. Represents the `valid-from` attribute.
+    pub valid_from: Option<NaiveDateTime>,
+    /// The end of the period in which the definition is valid. Represents the `valid-to` attribute.
+    pub valid_to: Option<NaiveDateTime>,
+    /// When the definition was last changed. Represents the `last-modified` attribute.
+    pub last_modified: Option<NaiveDateTime>,
+    /// The schema of the file. Represents the `xsi:schemaLocation` attribute.
+    pub schema_location: Option<String>,
+}
+
+impl Instrument {
+    /// Check if the definition is valid at the given time, like Mantid does when it selects the definition of a run. \
+    /// A missing `valid-from` or `valid-to` does not restrict the period.
+    /// # Example
+    /// ```
+    /// use mantid_idf::DetectorDefinition;
+    ///
+    /// let content = r#"
+    ///     <instrument name="Test" valid-from="2024-10-18 00:00:01" valid-to="2100-01-31 23:59:59">
+    ///     </instrument>
+    /// "#;
+    ///
+    /// let instrument = DetectorDefinition::from_str(content).unwrap().instrument;
+    ///
+    /// let date = chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    ///
+    /// assert!(instrument.is_valid_at(date));
+    /// ```
+    pub fn is_valid_at(&self, date_time: NaiveDateTime) -> bool {
+        self.valid_from
+            .is_none_or(|valid_from| valid_from <= date_time)
+            && self.valid_to.is_none_or(|valid_to| date_time <= valid_to)
+    }
 }
 
 /// Represents a component in the instrument definition file.
@@ -37,9 +102,20 @@ pub struct Component {
     /// Represents the `locations` tag in the IDF file. \
     /// Contains the translation and rotation of the component for multiple elements.
     pub locations: Vec<Locations>,
+    /// The name of the `idlist` that assigns the ids to the detectors of the component.
+    pub idlist: Option<String>,
     /// Other attributes that the component can have. \
     /// "Catch-all" for attributes to avoid many `Option` fields. TODO: Better solution?
     pub other_attributes: BTreeMap<String, String>,
+    /// The parameters defined inside of the component.
+    pub parameters: Parameters,
+    /// Parameters that only apply to some of the locations of the component, e.g. from a `component-link` to the
+    /// name of a single location. \
+    /// The key is the index of the location, counting the `location`s first and then the elements of the `locations`.
+    pub location_parameters: BTreeMap<usize, Parameters>,
+    /// The position of the component in the side-by-side view of the Mantid instrument view. \
+    /// Represents the `side-by-side-view-location` tag.
+    pub side_by_side_view_location: Option<Point2<f32>>,
 }
 
 impl Component {
@@ -47,16 +123,106 @@ impl Component {
     /// Checks if the type name is empty. \
     /// TODO: Find a better way to represent the root component.
     pub fn is_root(&self) -> bool {
-        self.type_name == ""
+        self.type_name.is_empty()
+    }
+
+    /// Check if the component is known under the given name. \
+    /// A component is named after its locations, or after its type if the location has no name. \
+    /// A named location hides the name of the type, like in Mantid.
+    pub fn has_name(&self, name: &str) -> bool {
+        !self.location_indices_by_name(name).is_empty()
     }
 
-    /// Push a new translation to the last location.
-    pub fn push_rotation(&mut self, rotation: Rotation) {
+    /// The names of all locations of the component, counting the `location`s first and then the elements of the
+    /// `locations`. \
+    /// A component without any location is named after its type.
+    pub fn location_names(&self) -> Vec<String> {
+        if self.location.is_empty() && self.locations.is_empty() {
+            return vec![self.type_name.clone()];
+        }
+
         self.location
-            .last_mut()
-            .get_or_insert(&mut Location::default())
-            .rotation
-            .push(rotation);
+            .iter()
+            .map(|location| self.location_name(location))
+            .chain(self.locations.iter().flat_map(|locations| {
+                (0..locations.n_elements)
+                    .map(|element| self.locations_element_name(locations, element))
+            }))
+            .collect()
+    }
+
+    /// The indices of the locations with the given name, see `Component::location_names`.
+    /// # Example
+    /// ```
+    /// use mantid_idf::structs::{Component, Location};
+    ///
+    /// let tube = Component {
+    ///     type_name: "tube".to_string(),
+    ///     location: vec![
+    ///         Location { name: "tube1".to_string(), ..Default::default() },
+    ///         Location::default(),
+    ///     ],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(tube.location_indices_by_name("tube1"), [0]);
+    /// assert_eq!(tube.location_indices_by_name("tube"), [1]);
+    /// ```
+    pub fn location_indices_by_name(&self, name: &str) -> Vec<usize> {
+        self.location_names()
+            .iter()
+            .enumerate()
+            .filter(|(_, location_name)| *location_name == name)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// The name of a location of the component, the name of its type if the location has no name.
+    pub fn location_name(&self, location: &Location) -> String {
+        match location.name.is_empty() {
+            true => self.type_name.clone(),
+            false => location.name.clone(),
+        }
+    }
+
+    /// The name of an element of the locations of the component, the name of its type if the locations have no name.
+    pub fn locations_element_name(&self, locations: &Locations, element: u32) -> String {
+        match locations.name.is_empty() {
+            true => self.type_name.clone(),
+            false => locations.element_name(element),
+        }
+    }
+
+    /// Check if every location of the component is left out by every location of the parent (see `Location::exclude`).
+    /// # Example
+    /// ```
+    /// use mantid_idf::structs::{Component, Location};
+    ///
+    /// let tube = Component {
+    ///     type_name: "tube".to_string(),
+    ///     location: vec![Location { name: "tube7".to_string(), ..Default::default() }],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let bank = Component {
+    ///     type_name: "bank".to_string(),
+    ///     location: vec![Location { exclude: vec!["tube7".to_string()], ..Default::default() }],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert!(tube.is_excluded_by(&bank));
+    /// ```
+    pub fn is_excluded_by(&self, parent: &Component) -> bool {
+        if parent.location.is_empty() || !parent.locations.is_empty() {
+            return false;
+        }
+
+        let names = self.location_names();
+
+        parent
+            .location
+            .iter()
+            .all(|location| names.iter().all(|name| location.exclude.contains(name)))
     }
 }
 
@@ -67,49 +233,221 @@ impl Component {
 pub struct Location {
     /// The name of the location.
     pub name: String,
-    /// The translations of the component.
+    /// The translations of the component, given by the attributes of the location.
     pub translation: Vec<Translation>,
-    /// The rotations of the component.
+    /// The rotations of the component, given by the attributes of the location.
     pub rotation: Vec<Rotation>,
+    /// The `rot` and `trans` tags inside of the location. \
+    /// They are applied in the coordinate frame given by the attributes of the location.
+    pub nested: Vec<NestedTransform>,
+    /// The `facing` tag inside of the location.
+    pub facing: Option<Facing>,
+    /// The names of the sub-parts (the components of the type of the component) that are left out at this location. \
+    /// Represents the `exclude` tags inside of the location.
+    pub exclude: Vec<String>,
+    /// The position used for unit conversions if it differs from the physical position, e.g. for the analyser
+    /// detectors of indirect geometry instruments. Relative to the parent like the location itself. \
+    /// Represents the `neutronic` tag inside of the location.
+    pub neutronic: Option<Box<Location>>,
 }
 
 impl Location {
-    pub(crate) fn to_new_translations_and_rotations(
-        &self,
-    ) -> (Vec<Translation3<f32>>, Vec<Rotation3<f32>>) {
-        let mut new_translations = Vec::new();
+    /// The transformation of the location from the local coordinates of the component to the coordinates of its parent. \
+    /// The rotation of the attributes is applied before their translation, the nested transforms before both:
+    /// `T * R * nested`.
+    /// # Example
+    /// ```
+    /// use mantid_idf::{defaults::Defaults, structs::{Location, NestedTransform, Rotation, Transform, Translation}, Point};
+    ///
+    /// // <location x="1"><rot val="90"><trans x="1"/></rot></location>
+    /// let location = Location {
+    ///     translation: vec![Translation::Cartesian(Point::new(1.0, 0.0, 0.0))],
+    ///     nested: vec![NestedTransform {
+    ///         transform: Transform::Rotation(Rotation { rot: 90.0, ..Default::default() }),
+    ///         children: vec![NestedTransform {
+    ///             transform: Transform::Translation(Translation::Cartesian(Point::new(1.0, 0.0, 0.0))),
+    ///             children: vec![],
+    ///         }],
+    ///     }],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let position = location.to_isometry(&Defaults::default()) * Point::origin();
+    ///
+    /// assert!((position - Point::new(1.0, 1.0, 0.0)).norm() < 1e-6);
+    /// ```
+    pub fn to_isometry(&self, defaults: &Defaults) -> Isometry3<f32> {
+        let translations = self
+            .translation
+            .iter()
+            .map(|translation| Translation3::from(translation.to_cartesian(defaults)))
+            .collect::<Vec<_>>();
+
+        let rotations = self
+            .rotation
+            .iter()
+            .map(|rotation| rotation.to_rotation3(defaults))
+            .collect::<Vec<_>>();
 
-        for translation in self.translation.iter() {
-            let translation = nalgebra::Translation3::from(translation.clone().into_cartesian());
+        self.nested.iter().fold(
+            isometry_from_translations_and_rotations(&translations, &rotations),
+            |isometry, nested| isometry * nested.to_isometry(defaults),
+        )
+    }
 
-            new_translations.push(translation);
-        }
+    /// The sum of the spherical translations of the attributes in metres and radians,
+    /// `None` if the location has no spherical translation.
+    pub(crate) fn spherical_offset(&self, defaults: &Defaults) -> Option<Point> {
+        self.translation
+            .iter()
+            .filter_map(|translation| translation.to_spherical(defaults))
+            .reduce(|sum, spherical| sum + spherical.coords)
+    }
 
-        let mut new_rotations = Vec::new();
+    /// Rotates the transformation of the component (in the coordinates of the instrument) according to the `facing`
+    /// tag of the location or, if the location has none, the `components-are-facing` default.
+    pub fn apply_facing(&self, isometry: Isometry3<f32>, defaults: &Defaults) -> Isometry3<f32> {
+        apply_facing(isometry, self.facing.as_ref(), defaults)
+    }
 
-        for rotation in self.rotation.iter() {
-            let rotation = nalgebra::Rotation3::from_axis_angle(
-                &nalgebra::Unit::new_normalize(nalgebra::Vector3::new(
-                    rotation.axis.x,
-                    rotation.axis.y,
-                    rotation.axis.z,
-                )),
-                rotation.rot.to_radians(),
-            );
+    /// Adds a nested transform inside of the innermost open transform given by `path`
+    /// (indices of the children, starting at the `nested` of the location).
+    pub(crate) fn push_nested(&mut self, path: &[usize], nested: NestedTransform) -> usize {
+        let mut children = &mut self.nested;
 
-            new_rotations.push(rotation);
+        for index in path {
+            children = &mut children[*index].children;
         }
-        (new_translations, new_rotations)
+
+        children.push(nested);
+
+        children.len() - 1
+    }
+}
+
+/// A `rot` or `trans` tag inside of a location. \
+/// Nested tags are applied in the coordinate frame of their parent tag, like Mantid does. \
+/// https://docs.mantidproject.org/nightly/concepts/InstrumentDefinitionFile.html#using-location
+#[derive(Debug, Clone)]
+pub struct NestedTransform {
+    /// The transform of the tag.
+    pub transform: Transform,
+    /// The tags inside of the tag.
+    pub children: Vec<NestedTransform>,
+}
+
+impl NestedTransform {
+    /// The transform of the tag combined with the transforms of its children.
+    pub fn to_isometry(&self, defaults: &Defaults) -> Isometry3<f32> {
+        let isometry = match &self.transform {
+            Transform::Rotation(rotation) => Isometry3::from_parts(
+                Translation3::identity(),
+                UnitQuaternion::from_rotation_matrix(&rotation.to_rotation3(defaults)),
+            ),
+            Transform::Translation(translation) => {
+                Isometry3::from(translation.to_cartesian(defaults).coords)
+            }
+        };
+
+        self.children.iter().fold(isometry, |isometry, child| {
+            isometry * child.to_isometry(defaults)
+        })
+    }
+}
+
+/// Represents the `facing` tag inside of a location. \
+/// The component is rotated such that its z-axis points from the facing point towards the component,
+/// i.e. its xy-plane faces the point. \
+/// https://docs.mantidproject.org/nightly/concepts/InstrumentDefinitionFile.html#using-facing
+#[derive(Debug, Default, Clone)]
+pub struct Facing {
+    /// The point the component is facing, in the coordinates of the instrument. \
+    /// `None` represents `val="none"`, which turns off the `components-are-facing` default for the location.
+    pub point: Option<Translation>,
+    /// A rotation around the z-axis of the component, applied before the component is rotated to face the point. \
+    /// Represents the `rot` attribute.
+    pub rot: Option<f32>,
+}
+
+/// Applies the `facing` tag (or the `components-are-facing` default if there is none) to the transformation of a
+/// component in the coordinates of the instrument.
+pub(crate) fn apply_facing(
+    mut isometry: Isometry3<f32>,
+    facing: Option<&Facing>,
+    defaults: &Defaults,
+) -> Isometry3<f32> {
+    let (point, rot) = match facing {
+        Some(facing) => (facing.point.as_ref(), facing.rot),
+        None => (defaults.components_are_facing.as_ref(), None),
+    };
+
+    if let Some(rot) = rot {
+        isometry.rotation *=
+            UnitQuaternion::from_axis_angle(&Vector3::z_axis(), defaults.angle_to_radians(rot));
+    }
+
+    let Some(point) = point else {
+        return isometry;
+    };
+
+    let facing_direction = isometry.translation.vector - point.to_cartesian(defaults).coords;
+
+    // A component at the facing point keeps its orientation, like in Mantid
+    if facing_direction.norm() == 0.0 {
+        return isometry;
     }
+
+    // The direction in the local coordinates of the component
+    let facing_direction = isometry
+        .rotation
+        .inverse_transform_vector(&facing_direction);
+
+    isometry.rotation *= UnitQuaternion::rotation_between(&Vector3::z(), &facing_direction)
+        .unwrap_or_else(|| {
+            UnitQuaternion::from_axis_angle(&Vector3::x_axis(), std::f32::consts::PI)
+        });
+
+    isometry
+}
+
+/// The transform of a `NestedTransform`.
+#[derive(Debug, Clone)]
+pub enum Transform {
+    /// Represents the `rot` tag.
+    Rotation(Rotation),
+    /// Represents the `trans` tag.
+    Translation(Translation),
+}
+
+/// Combines the translations and rotations of a location into one isometry. \
+/// The translations are added up, the rotations are combined from the outermost to the innermost.
+pub(crate) fn isometry_from_translations_and_rotations(
+    translations: &[Translation3<f32>],
+    rotations: &[Rotation3<f32>],
+) -> Isometry3<f32> {
+    let translation = translations
+        .iter()
+        .fold(Translation3::identity(), |sum, translation| {
+            sum * translation
+        });
+
+    let rotation = rotations
+        .iter()
+        .fold(Rotation3::identity(), |product, rotation| {
+            product * rotation
+        });
+
+    Isometry3::from_parts(translation, UnitQuaternion::from_rotation_matrix(&rotation))
 }
 
 /// Represents a translation in the IDF file.
 /// Can be in cartesian or spherical coordinates.
+/// The values are stored as they are written in the file, i.e. in the units of the `Defaults`.
 #[derive(Debug, Clone)]
 pub enum Translation {
     /// Represents a translation in cartesian coordinates.
     Cartesian(Point),
-    /// Represents a translation in spherical coordinates.
+    /// Represents a translation in spherical coordinates (`r`, `t`, `p`).
     Spherical(Point),
 }
 
@@ -121,22 +459,65 @@ impl Translation {
         }
     }
 
-    /// Convert the generic translation to cartesian coordinates.
-    pub fn into_cartesian(self) -> Point {
+    /// Convert the generic translation to cartesian coordinates in metres. \
+    /// The lengths and angles are converted according to the units of the `Defaults`.
+    /// # Example
+    /// ```
+    /// use mantid_idf::{defaults::Defaults, structs::Translation, Point};
+    ///
+    /// let translation = Translation::Spherical(Point::new(2.0, 90.0, 0.0));
+    ///
+    /// let point = translation.to_cartesian(&Defaults::default());
+    ///
+    /// assert!((point - Point::new(2.0, 0.0, 0.0)).norm() < 1e-6);
+    /// ```
+    pub fn to_cartesian(&self, defaults: &Defaults) -> Point {
         match self {
-            Self::Cartesian(v) => v,
-            Self::Spherical(v) => spherical_to_cartesian(v),
+            Self::Cartesian(v) => v.map(|length| defaults.length_to_metres(length)),
+            Self::Spherical(v) => spherical_to_cartesian(Point::new(
+                defaults.length_to_metres(v.x),
+                defaults.angle_to_radians(v.y),
+                defaults.angle_to_radians(v.z),
+            )),
+        }
+    }
+
+    /// The spherical coordinates of a spherical translation in metres and radians.
+    pub(crate) fn to_spherical(&self, defaults: &Defaults) -> Option<Point> {
+        match self {
+            Self::Cartesian(_) => None,
+            Self::Spherical(v) => Some(Point::new(
+                defaults.length_to_metres(v.x),
+                defaults.angle_to_radians(v.y),
+                defaults.angle_to_radians(v.z),
+            )),
+        }
+    }
+
+    /// Linearly interpolates between two translations. \
+    /// If both translations use the same coordinate system the interpolation is done in that coordinate system,
+    /// otherwise it is done in cartesian coordinates.
+    pub(crate) fn lerp(&self, end: &Translation, t: f32, defaults: &Defaults) -> Point {
+        match (self, end) {
+            (Self::Spherical(start), Self::Spherical(end)) => {
+                Self::Spherical(start.coords.lerp(&end.coords, t).into()).to_cartesian(defaults)
+            }
+            (start, end) => start
+                .to_cartesian(defaults)
+                .coords
+                .lerp(&end.to_cartesian(defaults).coords, t)
+                .into(),
         }
     }
 }
 
 /// Represents a rotation in the IDF file.
 /// Contains the rotation angle and the axis of rotation.
-/// The angle is in degrees.
+/// The angle is in the angle unit of the `Defaults` (degrees by default).
 /// The default axis is the z-axis.
 #[derive(Debug, Clone)]
 pub struct Rotation {
-    /// The rotation angle in degrees.
+    /// The rotation angle in the angle unit of the `Defaults`.
     pub rot: f32,
     /// The axis of rotation.
     pub axis: Point,
@@ -153,6 +534,28 @@ impl Default for Rotation {
     }
 }
 
+impl Rotation {
+    /// Convert the rotation to a `Rotation3`. \
+    /// The angle is converted according to the angle unit of the `Defaults`.
+    /// # Example
+    /// ```
+    /// use mantid_idf::{defaults::Defaults, structs::Rotation};
+    ///
+    /// let rotation = Rotation { rot: 90.0, ..Default::default() };
+    ///
+    /// let rotation = rotation.to_rotation3(&Defaults::default());
+    ///
+    /// assert!((rotation.angle() - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+    /// ```
+    pub fn to_rotation3(&self, defaults: &Defaults) -> Rotation3<f32> {
+        Self::rotation3_from_axis_angle(self.axis, defaults.angle_to_radians(self.rot))
+    }
+
+    fn rotation3_from_axis_angle(axis: Point, angle: f32) -> Rotation3<f32> {
+        Rotation3::from_axis_angle(&nalgebra::Unit::new_normalize(axis.coords), angle)
+    }
+}
+
 /// Represents the locations of a component for multiple elements.
 /// Contains a start and end translation and rotation.
 /// The translations and rotations are interpolated between the start and end values.
@@ -163,7 +566,7 @@ pub struct Locations {
     pub n_elements: u32,
     /// The name of the locations.
     pub name: String,
-    /// Where the name count starts. TODO: not properly implemented.
+    /// Where the name count starts. The elements are named `name` + count.
     pub name_count_start: u32,
     /// The start translation.
     pub start_translation: Option<Translation>,
@@ -177,64 +580,140 @@ pub struct Locations {
 }
 
 impl Locations {
-    pub(crate) fn to_new_translations_and_rotations(
+    /// The name of the element. \
+    /// Mantid appends the count (starting at `name_count_start`) to the name of the locations.
+    pub fn element_name(&self, element: u32) -> String {
+        format!("{}{}", self.name, self.name_count_start + element)
+    }
+
+    /// The interpolation factor of the element. \
+    /// The first element is at the start value and the last element at the end value.
+    fn interpolation_factor(&self, element: u32) -> f32 {
+        match self.n_elements {
+            0 | 1 => 0.0,
+            n_elements => element as f32 / (n_elements - 1) as f32,
+        }
+    }
+
+    fn to_new_translations_and_rotations(
         &self,
         element: u32,
+        defaults: &Defaults,
     ) -> (Vec<Translation3<f32>>, Vec<Rotation3<f32>>) {
-        {
-            let mut new_translations = Vec::new();
-
-            if let Some(start_translation) = self.start_translation.as_ref() {
-                match self.end_translation.as_ref() {
-                    Some(end_translation) => {
-                        let translation = start_translation.clone().into_cartesian().lerp(
-                            &end_translation.clone().into_cartesian(),
-                            element as f32 / self.n_elements as f32,
-                        );
-
-                        new_translations.push(Translation3::from(translation));
-                    }
-                    None => {
-                        let translation = start_translation.clone().into_cartesian();
-
-                        new_translations.push(Translation3::from(translation));
-                    }
-                }
-            }
+        let t = self.interpolation_factor(element);
 
-            let mut new_rotations = Vec::new();
-
-            if let Some(start_rotation) = self.start_rotation.as_ref() {
-                match self.end_rotation.as_ref() {
-                    Some(end_rotation) => {
-                        let rotation = nalgebra::Rotation3::from_axis_angle(
-                            &nalgebra::Unit::new_normalize(nalgebra::Vector3::new(
-                                start_rotation.axis.x,
-                                start_rotation.axis.y,
-                                start_rotation.axis.z,
-                            )),
-                            start_rotation.rot
-                                + (end_rotation.rot - start_rotation.rot)
-                                    * (element as f32 / self.n_elements as f32),
-                        );
-
-                        new_rotations.push(rotation);
-                    }
-                    None => {
-                        let rotation = nalgebra::Rotation3::from_axis_angle(
-                            &nalgebra::Unit::new_normalize(nalgebra::Vector3::new(
-                                start_rotation.axis.x,
-                                start_rotation.axis.y,
-                                start_rotation.axis.z,
-                            )),
-                            start_rotation.rot.to_radians(),
-                        );
-
-                        new_rotations.push(rotation);
-                    }
-                }
-            }
-            (new_translations, new_rotations)
+        let mut new_translations = Vec::new();
+
+        if let Some(start_translation) = self.start_translation.as_ref() {
+            let translation = match self.end_translation.as_ref() {
+                Some(end_translation) => start_translation.lerp(end_translation, t, defaults),
+                None => start_translation.to_cartesian(defaults),
+            };
+
+            new_translations.push(Translation3::from(translation));
+        }
+
+        let mut new_rotations = Vec::new();
+
+        if let Some(start_rotation) = self.start_rotation.as_ref() {
+            let rotation = match self.end_rotation.as_ref() {
+                Some(end_rotation) => Rotation::rotation3_from_axis_angle(
+                    start_rotation.axis,
+                    defaults.angle_to_radians(
+                        start_rotation.rot + (end_rotation.rot - start_rotation.rot) * t,
+                    ),
+                ),
+                None => start_rotation.to_rotation3(defaults),
+            };
+
+            new_rotations.push(rotation);
+        }
+
+        (new_translations, new_rotations)
+    }
+
+    /// The spherical translation of the element in metres and radians, see `Location::spherical_offset`. \
+    /// `None` if the start and end translations are not both spherical.
+    pub(crate) fn spherical_offset(&self, element: u32, defaults: &Defaults) -> Option<Point> {
+        let start = self.start_translation.as_ref()?.to_spherical(defaults)?;
+
+        match self.end_translation.as_ref() {
+            Some(end) => Some(
+                start
+                    .coords
+                    .lerp(
+                        &end.to_spherical(defaults)?.coords,
+                        self.interpolation_factor(element),
+                    )
+                    .into(),
+            ),
+            None => Some(start),
+        }
+    }
+
+    /// The transformation of the element, see `Location::to_isometry`.
+    pub fn to_isometry(&self, element: u32, defaults: &Defaults) -> Isometry3<f32> {
+        let (translations, rotations) = self.to_new_translations_and_rotations(element, defaults);
+
+        isometry_from_translations_and_rotations(&translations, &rotations)
+    }
+
+    /// Converts the element to a single `Location` with the same transformation and name. \
+    /// Spherical translations stay spherical, so that `offsets spherical="delta"` still applies to them.
+    /// # Example
+    /// ```
+    /// use mantid_idf::{defaults::Defaults, structs::{Locations, Translation}, Point};
+    ///
+    /// let locations = Locations {
+    ///     n_elements: 3,
+    ///     name: "tube".to_string(),
+    ///     name_count_start: 1,
+    ///     start_translation: Some(Translation::Cartesian(Point::new(0.0, 0.0, 0.0))),
+    ///     end_translation: Some(Translation::Cartesian(Point::new(2.0, 0.0, 0.0))),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let defaults = Defaults::default();
+    /// let location = locations.to_location(1, &defaults);
+    ///
+    /// assert_eq!(location.name, "tube2");
+    /// assert_eq!(location.to_isometry(&defaults), locations.to_isometry(1, &defaults));
+    /// ```
+    pub fn to_location(&self, element: u32, defaults: &Defaults) -> Location {
+        let t = self.interpolation_factor(element);
+
+        let translation = match (
+            self.start_translation.as_ref(),
+            self.end_translation.as_ref(),
+        ) {
+            (Some(Translation::Spherical(start)), Some(Translation::Spherical(end))) => Some(
+                Translation::Spherical(start.coords.lerp(&end.coords, t).into()),
+            ),
+            (Some(start), Some(end)) => Some(Translation::Cartesian(
+                start
+                    .lerp(end, t, defaults)
+                    .map(|length| defaults.metres_to_length(length)),
+            )),
+            (Some(start), None) => Some(start.clone()),
+            (None, _) => None,
+        };
+
+        let rotation = self.start_rotation.as_ref().map(|start| Rotation {
+            rot: match self.end_rotation.as_ref() {
+                Some(end) => start.rot + (end.rot - start.rot) * t,
+                None => start.rot,
+            },
+            axis: start.axis,
+        });
+
+        Location {
+            name: match self.name.is_empty() {
+                true => String::new(),
+                false => self.element_name(element),
+            },
+            translation: translation.into_iter().collect(),
+            rotation: rotation.into_iter().collect(),
+            ..Default::default()
         }
     }
 }
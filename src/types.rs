@@ -2,7 +2,9 @@
 
 use std::collections::BTreeMap;
 
-use crate::structs::Type;
+use anyhow::Context;
+
+use crate::{defaults::Defaults, shapes::Shapes, structs::Type};
 
 /// Struct to hold the types defined in the IDF file.
 #[derive(Debug, Clone)]
@@ -19,6 +21,100 @@ impl Default for Types {
     }
 }
 
+impl Types {
+    /// Replaces the components of all types with `combine_components_into_one_shape` by the combined shape. \
+    /// Has to be called after all types are parsed, as the components can use types that are defined later.
+    pub(crate) fn combine_component_shapes(&mut self, defaults: &Defaults) -> anyhow::Result<()> {
+        let combined_shapes = self
+            .values()
+            .filter(|type_| type_.combine_components_into_one_shape)
+            .map(|type_| {
+                let shape = self
+                    .combined_shape(type_, defaults, &mut Vec::new())
+                    .context(format!(
+                        "could not combine the components of type {} into one shape",
+                        type_.name
+                    ))?;
+
+                Ok((type_.name.clone(), shape))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        for (name, shape) in combined_shapes {
+            let type_ = self.get_mut(&name).expect("type was found above");
+
+            type_.shape = Some(shape);
+            type_.components.clear();
+        }
+
+        Ok(())
+    }
+
+    /// The shapes of the components of the type, moved to their locations and combined by the algebra of the type. \
+    /// Without an algebra the union of the shapes is used. \
+    /// `parents` are the names of the types that are currently being combined, a type that contains itself is an error.
+    fn combined_shape(
+        &self,
+        type_: &Type,
+        defaults: &Defaults,
+        parents: &mut Vec<String>,
+    ) -> anyhow::Result<Shapes> {
+        if parents.contains(&type_.name) {
+            return Err(anyhow::anyhow!(
+                "type {} contains itself: {} -> {}",
+                type_.name,
+                parents.join(" -> "),
+                type_.name
+            ));
+        }
+
+        parents.push(type_.name.clone());
+
+        let mut named_shapes = Vec::new();
+
+        for component in type_.components.iter() {
+            let component_type = self
+                .get(&component.type_name)
+                .context(format!("could not find type {}", component.type_name))?;
+
+            let shape = match component_type.combine_components_into_one_shape {
+                true => self.combined_shape(component_type, defaults, parents)?,
+                false => component_type
+                    .shape
+                    .clone()
+                    .context(format!("type {} has no shape", component_type.name))?,
+            };
+
+            for location in component.location.iter() {
+                named_shapes.push((
+                    location.name.clone(),
+                    shape.transformed(&location.to_isometry(defaults))?,
+                ));
+            }
+
+            for locations in component.locations.iter() {
+                for element in 0..locations.n_elements {
+                    named_shapes.push((
+                        locations.element_name(element),
+                        shape.transformed(&locations.to_isometry(element, defaults))?,
+                    ));
+                }
+            }
+        }
+
+        let mut shape = Shapes::from_named_algebra(named_shapes, type_.algebra.as_deref())?
+            .context("no components to combine")?;
+
+        if let Some(location) = type_.translate_rotate_combined_shape_to.as_ref() {
+            shape = shape.transformed(&location.to_isometry(defaults).inverse())?;
+        }
+
+        parents.pop();
+
+        Ok(shape)
+    }
+}
+
 impl std::ops::Deref for Types {
     type Target = BTreeMap<String, Type>;
 
@@ -51,6 +147,9 @@ pub enum SpecialTypes {
     /// Represents a structured detector. Used to quickly generated a detector with a structured (irregular geometry) shape. \
     /// https://docs.mantidproject.org/nightly/concepts/InstrumentDefinitionFile.html#creating-structured-detectors
     StructuredDetector,
+    /// Represents a grid detector. Used to quickly generate a three dimensional grid of voxels. \
+    /// https://docs.mantidproject.org/nightly/concepts/InstrumentDefinitionFile.html#creating-grid-detectors
+    GridDetector,
     /// Represents the source.
     Source,
     /// Represents the sample position.
@@ -76,6 +175,10 @@ impl std::str::FromStr for SpecialTypes {
             "structuredDetector" => Ok(Self::StructuredDetector),
             "structureddetector" => Ok(Self::StructuredDetector),
             "structured_detector" => Ok(Self::StructuredDetector),
+            "GridDetector" => Ok(Self::GridDetector),
+            "gridDetector" => Ok(Self::GridDetector),
+            "griddetector" => Ok(Self::GridDetector),
+            "grid_detector" => Ok(Self::GridDetector),
             "Source" => Ok(Self::Source),
             "source" => Ok(Self::Source),
             "SamplePos" => Ok(Self::SamplePos),
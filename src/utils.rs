@@ -5,7 +5,7 @@ use anyhow::Context;
 use crate::Point;
 
 pub(crate) fn add_suffix(key: &[u8], suffix: &str) -> Vec<u8> {
-    key.into_iter()
+    key.iter()
         .chain(suffix.as_bytes())
         .copied()
         .collect::<Vec<_>>()
@@ -14,20 +14,25 @@ pub(crate) fn add_suffix(key: &[u8], suffix: &str) -> Vec<u8> {
 pub(crate) fn parse_attribute<T>(attribute: &[u8]) -> anyhow::Result<T>
 where
     T: std::str::FromStr,
-    <T as std::str::FromStr>::Err: std::error::Error + Send + Sync + 'static,
+    <T as std::str::FromStr>::Err: Into<anyhow::Error>,
 {
     let string = std::str::from_utf8(attribute).context(format!(
         "could not convert attribute to string: {:?}",
         attribute
     ))?;
 
-    string.parse::<T>().context(format!(
-        "could not parse string: {:?} to {:?}",
-        string,
-        std::any::type_name::<T>()
-    ))
+    string
+        .parse::<T>()
+        .map_err(Into::<anyhow::Error>::into)
+        .context(format!(
+            "could not parse string: {:?} to {:?}",
+            string,
+            std::any::type_name::<T>()
+        ))
 }
 
+/// Converts a point in spherical coordinates (`r`, `theta`, `phi`) to cartesian coordinates. \
+/// The angles have to be in radians. `theta` is the polar angle from the z-axis and `phi` the azimuthal angle from the x-axis.
 pub(crate) fn spherical_to_cartesian(point_in_spherical_coordinates: Point) -> Point {
     let r = point_in_spherical_coordinates.x;
     let theta = point_in_spherical_coordinates.y;
@@ -40,6 +45,37 @@ pub(crate) fn spherical_to_cartesian(point_in_spherical_coordinates: Point) -> P
     Point::new(x, y, z)
 }
 
+/// Parses a timestamp of the IDF file, e.g. `2024-10-18 12:01:44.11111`. \
+/// The date and the time can also be separated by a `T` (ISO 8601) and the time can be left out.
+pub(crate) fn parse_timestamp(timestamp: &str) -> anyhow::Result<chrono::NaiveDateTime> {
+    let timestamp = timestamp.trim();
+
+    for format in ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"] {
+        if let Ok(date_time) = chrono::NaiveDateTime::parse_from_str(timestamp, format) {
+            return Ok(date_time);
+        }
+    }
+
+    chrono::NaiveDate::parse_from_str(timestamp, "%Y-%m-%d")
+        .map(|date| date.and_time(chrono::NaiveTime::MIN))
+        .context(format!("could not parse timestamp {:?}", timestamp))
+}
+
+/// Converts a point in cartesian coordinates to spherical coordinates (`r`, `theta`, `phi`). \
+/// Inverse of `spherical_to_cartesian`, the angles are in radians.
+pub(crate) fn cartesian_to_spherical(point: Point) -> Point {
+    let r = point.coords.norm();
+
+    if r == 0.0 {
+        return Point::origin();
+    }
+
+    let theta = (point.z / r).clamp(-1.0, 1.0).acos();
+    let phi = point.y.atan2(point.x);
+
+    Point::new(r, theta, phi)
+}
+
 /// The axes of a 3D coordinate system.
 #[allow(missing_docs)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -49,6 +85,17 @@ pub enum Axes {
     Z,
 }
 
+impl Axes {
+    /// Returns the unit vector along the axis.
+    pub fn unit_vector(&self) -> nalgebra::Vector3<f32> {
+        match self {
+            Self::X => nalgebra::Vector3::x(),
+            Self::Y => nalgebra::Vector3::y(),
+            Self::Z => nalgebra::Vector3::z(),
+        }
+    }
+}
+
 impl std::str::FromStr for Axes {
     type Err = anyhow::Error;
 
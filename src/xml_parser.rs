@@ -7,19 +7,23 @@ mod try_match_bytes_start;
 use std::collections::BTreeMap;
 use std::sync::Arc;
 
-use quick_xml::events::Event;
+use quick_xml::events::{BytesStart, Event};
 use quick_xml::reader::Reader;
 
 use anyhow::Context;
-use try_match_bytes_start::TryMatchBytesStart;
+use try_match_bytes_start::{get_required_attribute, TryMatchBytesStart};
 
 use crate::component_tree::ComponentTree;
+use crate::defaults::Defaults;
 use crate::detector_definition::DetectorDefinition;
 
 use crate::idlists::IDList;
-use crate::shapes::Hexahedron;
+use crate::parameter_file::{ComponentLink, ParameterFile};
+use crate::parameters::Parameter;
+use crate::shapes::{BoundingBox, ShapeBuilder, Shapes};
 use crate::structs::*;
 use crate::types::Types;
+use crate::utils::parse_attribute;
 
 pub(crate) fn detector_definition_from_str(str: &str) -> anyhow::Result<DetectorDefinition> {
     let mut reader = Reader::from_str(str);
@@ -28,20 +32,45 @@ pub(crate) fn detector_definition_from_str(str: &str) -> anyhow::Result<Detector
 
     let mut buf = Vec::new();
 
-    let mut components = BTreeMap::<String, Component>::new();
+    let mut components = Vec::<Component>::new();
 
     let mut types = Types::default();
 
     let mut id_lists = BTreeMap::<String, IDList>::new();
 
+    let mut defaults = Defaults::default();
+
+    let mut instrument: Option<Instrument> = None;
+
+    let mut current_defaults: Option<Defaults> = None;
+
+    let mut current_parameter: Option<Parameter> = None;
+
+    let mut component_links = Vec::<ComponentLink>::new();
+
+    let mut current_component_link: Option<ComponentLink> = None;
+
     let mut current_component = None;
 
     let mut current_type: Option<Type> = None;
 
-    let mut current_hexahedron: Option<Hexahedron> = None;
+    let mut current_shape: Option<ShapeBuilder> = None;
+
+    // The shapes and the algebra of the current type, combined at the end of the type
+    let mut current_shapes = Vec::<Shapes>::new();
+
+    let mut current_algebra: Option<String> = None;
+
+    let mut current_bounding_box: Option<BoundingBox> = None;
 
     let mut current_id_list = None;
 
+    // The indices of the open `rot`/`trans` tags inside of the current location
+    let mut current_nested_path = Vec::<usize>::new();
+
+    // `true` inside of a `neutronic` tag, the `rot`/`trans` tags then belong to the neutronic location
+    let mut current_in_neutronic = false;
+
     loop {
         match reader.read_event_into(&mut buf) {
             Err(error) => {
@@ -53,6 +82,38 @@ pub(crate) fn detector_definition_from_str(str: &str) -> anyhow::Result<Detector
             }
             Ok(Event::Eof) => break,
             Ok(Event::Start(bytes_start)) => {
+                if Instrument::try_match_bytes_start(&mut instrument, &bytes_start, None)
+                    .context("could not parse instrument from start event")?
+                    .match_found()
+                {
+                    continue;
+                }
+
+                if Defaults::try_match_bytes_start(&mut current_defaults, &bytes_start, None)
+                    .context("could not parse defaults from start event")?
+                    .match_found()
+                {
+                    continue;
+                }
+
+                if Parameter::try_match_bytes_start(&mut current_parameter, &bytes_start, None)
+                    .context("could not parse parameter from start event")?
+                    .match_found()
+                {
+                    continue;
+                }
+
+                if ComponentLink::try_match_bytes_start(
+                    &mut current_component_link,
+                    &bytes_start,
+                    None,
+                )
+                .context("could not parse component-link from start event")?
+                .match_found()
+                {
+                    continue;
+                }
+
                 if Component::try_match_bytes_start(&mut current_component, &bytes_start, None)
                     .context("could not parse component from start event")?
                     .match_found()
@@ -67,8 +128,15 @@ pub(crate) fn detector_definition_from_str(str: &str) -> anyhow::Result<Detector
                     continue;
                 }
 
-                if Hexahedron::try_match_bytes_start(&mut current_hexahedron, &bytes_start, None)
-                    .context("could not parse hexahedron from start event")?
+                if BoundingBox::try_match_bytes_start(&mut current_bounding_box, &bytes_start, None)
+                    .context("could not parse bounding-box from start event")?
+                    .match_found()
+                {
+                    continue;
+                }
+
+                if ShapeBuilder::try_match_bytes_start(&mut current_shape, &bytes_start, None)
+                    .context("could not parse shape from start event")?
                     .match_found()
                 {
                     continue;
@@ -81,36 +149,41 @@ pub(crate) fn detector_definition_from_str(str: &str) -> anyhow::Result<Detector
                     continue;
                 }
 
+                if push_location_child(&bytes_start, &mut current_component)? {
+                    continue;
+                }
+
                 let mut location = None;
 
                 Location::try_match_bytes_start(&mut location, &bytes_start, None)
                     .context("could not parse location from start event")?;
 
-                if let Some(mut location) = location {
-                    let component = current_component.get_or_insert_default();
-
-                    if location.name.is_empty() {
-                        location.name = component.type_name.clone();
-                    }
+                if let Some(location) = location {
+                    push_location(location, &bytes_start, &mut current_component)?;
 
-                    component.location.push(location);
+                    current_in_neutronic = bytes_start.name().as_ref() == b"neutronic";
 
                     continue;
                 }
 
-                let mut rotation = None;
+                let mut nested_transform = None;
 
-                Rotation::try_match_bytes_start(&mut rotation, &bytes_start, None)
-                    .context("could not parse rotation from start event")?;
+                NestedTransform::try_match_bytes_start(&mut nested_transform, &bytes_start, None)
+                    .context("could not parse rot/trans from start event")?;
 
-                if let Some(rotation) = rotation {
-                    current_component
-                        .get_or_insert_default()
-                        .push_rotation(rotation);
+                if let Some(nested_transform) = nested_transform {
+                    let index = push_nested_transform(
+                        nested_transform,
+                        &current_nested_path,
+                        current_in_neutronic,
+                        &mut current_component,
+                    )?;
+
+                    current_nested_path.push(index);
                 }
             }
             Ok(Event::End(bytes_end)) => match bytes_end.name().as_ref() {
-                // TODO: error if end was reached without a component/type/hexahedron?
+                // TODO: error if end was reached without a component/type/shape?
                 b"component" => {
                     if let Some(component) = current_component.take() {
                         match current_type.as_mut() {
@@ -118,20 +191,48 @@ pub(crate) fn detector_definition_from_str(str: &str) -> anyhow::Result<Detector
                                 type_.components.push(component);
                             }
                             None => {
-                                components.insert(component.type_name.clone(), component);
+                                components.push(component);
                             }
                         }
                     }
                 }
                 b"type" => {
-                    if let Some(type_) = current_type.take() {
+                    if let Some(mut type_) = current_type.take() {
+                        type_.algebra = current_algebra.take();
+
+                        // The shapes of combined components are only known after all types are parsed
+                        if !type_.combine_components_into_one_shape {
+                            type_.shape = Shapes::from_algebra(
+                                std::mem::take(&mut current_shapes),
+                                type_.algebra.as_deref(),
+                            )
+                            .context(format!("could not combine shapes of type {}", type_.name))?;
+                        }
+
                         types.insert(type_.name.clone(), type_);
                     }
                 }
-                b"hexahedron" => {
-                    if let Some(hexahedron) = current_hexahedron.take() {
-                        current_type.get_or_insert_default().shape =
-                            Some(crate::shapes::Shapes::Hexahedron(hexahedron));
+                b"rot" | b"trans" => {
+                    current_nested_path.pop();
+                }
+                b"neutronic" => {
+                    current_in_neutronic = false;
+                }
+                b"bounding-box" => {
+                    if let Some(bounding_box) = current_bounding_box.take() {
+                        current_type.get_or_insert_default().bounding_box = Some(bounding_box);
+                    }
+                }
+                tag if current_shape
+                    .as_ref()
+                    .is_some_and(|shape| shape.tag.as_bytes() == tag) =>
+                {
+                    if let Some(shape) = current_shape.take() {
+                        current_shapes.push(
+                            shape
+                                .build()
+                                .context(format!("could not build shape {}", shape.id))?,
+                        );
                     }
                 }
                 b"idlist" => {
@@ -139,18 +240,83 @@ pub(crate) fn detector_definition_from_str(str: &str) -> anyhow::Result<Detector
                         id_lists.insert(id_list.name.clone(), id_list);
                     }
                 }
+                b"parameter" => {
+                    if let Some(parameter) = current_parameter.take() {
+                        insert_parameter(
+                            parameter,
+                            &mut current_component_link,
+                            &mut current_component,
+                            &mut current_type,
+                        )?;
+                    }
+                }
+                b"component-link" => {
+                    if let Some(component_link) = current_component_link.take() {
+                        component_links.push(component_link);
+                    }
+                }
+                b"defaults" => {
+                    if let Some(current_defaults) = current_defaults.take() {
+                        defaults = current_defaults;
+                    }
+                }
                 _ => (),
             },
             Ok(Event::Empty(bytes_start)) => {
+                if Instrument::try_match_bytes_start(&mut instrument, &bytes_start, None)
+                    .context("could not parse instrument from empty event")?
+                    .match_found()
+                {
+                    continue;
+                }
+
+                if Defaults::try_match_bytes_start(&mut current_defaults, &bytes_start, None)
+                    .context("could not parse defaults from empty event")?
+                    .match_found()
+                {
+                    continue;
+                }
+
+                if Parameter::try_match_bytes_start(&mut current_parameter, &bytes_start, None)
+                    .context("could not parse parameter from empty event")?
+                    .match_found()
+                {
+                    // A self-closing `parameter` tag has no end event
+                    if bytes_start.name().as_ref() == b"parameter" {
+                        if let Some(parameter) = current_parameter.take() {
+                            insert_parameter(
+                                parameter,
+                                &mut current_component_link,
+                                &mut current_component,
+                                &mut current_type,
+                            )?;
+                        }
+                    }
+
+                    continue;
+                }
+
                 if Type::try_match_bytes_start(&mut current_type, &bytes_start, None)
                     .context("could not parse type from empty event")?
                     .match_found()
                 {
-                    let type_ = current_type
-                        .take()
-                        .context("Type is None even though it should not be")?;
+                    // A self-closing `type` tag has no end event
+                    if bytes_start.name().as_ref() == b"type" {
+                        let type_ = current_type
+                            .take()
+                            .context("Type is None even though it should not be")?;
+
+                        types.insert(type_.name.clone(), type_);
+                    }
+
+                    continue;
+                }
 
-                    types.insert(type_.name.clone(), type_);
+                if BoundingBox::try_match_bytes_start(&mut current_bounding_box, &bytes_start, None)
+                    .context("could not parse bounding-box from empty event")?
+                    .match_found()
+                {
+                    continue;
                 }
 
                 if IDList::try_match_bytes_start(&mut current_id_list, &bytes_start, None)
@@ -160,33 +326,51 @@ pub(crate) fn detector_definition_from_str(str: &str) -> anyhow::Result<Detector
                     continue;
                 }
 
+                if ShapeBuilder::try_match_bytes_start(&mut current_shape, &bytes_start, None)
+                    .context("could not parse shape from empty event")?
+                    .match_found()
+                {
+                    continue;
+                }
+
+                if bytes_start.name().as_ref() == b"algebra" {
+                    let algebra = bytes_start
+                        .try_get_attribute("val")?
+                        .context("could not get attribute val of algebra")?;
+
+                    current_algebra = Some(parse_attribute(&algebra.value)?);
+
+                    continue;
+                }
+
                 let mut location = None;
 
                 Location::try_match_bytes_start(&mut location, &bytes_start, None)
                     .context("could not parse location from empty event")?;
 
-                if let Some(mut location) = location {
-                    let component = current_component.get_or_insert_default();
-
-                    if location.name.is_empty() {
-                        location.name = component.type_name.clone();
-                    }
-
-                    component.location.push(location);
+                if let Some(location) = location {
+                    push_location(location, &bytes_start, &mut current_component)?;
 
                     continue;
                 }
 
-                let mut rotation = None;
+                let mut nested_transform = None;
 
-                Rotation::try_match_bytes_start(&mut rotation, &bytes_start, None)
-                    .context("could not parse rotation from empty event")?;
+                NestedTransform::try_match_bytes_start(&mut nested_transform, &bytes_start, None)
+                    .context("could not parse rot/trans from empty event")?;
 
-                if let Some(rotation) = rotation {
-                    current_component
-                        .get_or_insert_default()
-                        .push_rotation(rotation);
+                if let Some(nested_transform) = nested_transform {
+                    push_nested_transform(
+                        nested_transform,
+                        &current_nested_path,
+                        current_in_neutronic,
+                        &mut current_component,
+                    )?;
+
+                    continue;
+                }
 
+                if push_location_child(&bytes_start, &mut current_component)? {
                     continue;
                 }
 
@@ -203,9 +387,6 @@ pub(crate) fn detector_definition_from_str(str: &str) -> anyhow::Result<Detector
 
                     continue;
                 }
-
-                Hexahedron::try_match_bytes_start(&mut current_hexahedron, &bytes_start, None)
-                    .context("could not parse hexahedron from empty event")?;
             }
             Ok(Event::Text(_)) => (),
             _ => (),
@@ -214,14 +395,261 @@ pub(crate) fn detector_definition_from_str(str: &str) -> anyhow::Result<Detector
 
     buf.clear();
 
+    types
+        .combine_component_shapes(&defaults)
+        .context("could not combine component shapes")?;
+
+    // The instrument tag is optional, e.g. for a bare list of components and types
+    let instrument = instrument.unwrap_or_default();
+
     let types_pointer = Arc::new(types);
 
-    let component_trees =
-        ComponentTree::from_types_and_components(types_pointer.clone(), components);
+    let defaults_pointer = Arc::new(defaults);
+
+    let id_lists_pointer = Arc::new(id_lists);
+
+    let mut component_trees = ComponentTree::from_types_and_components(
+        types_pointer.clone(),
+        defaults_pointer.clone(),
+        id_lists_pointer.clone(),
+        components,
+    );
+
+    let unmatched_component_links = component_links
+        .iter()
+        .filter(|component_link| {
+            !component_trees.apply_component_link(component_link, &instrument.name)
+        })
+        .map(|component_link| component_link.name.clone())
+        .collect();
 
     Ok(DetectorDefinition {
+        instrument,
         types: types_pointer,
         component_tree: component_trees,
-        id_lists,
+        id_lists: id_lists_pointer,
+        defaults: defaults_pointer,
+        unmatched_component_links,
     })
 }
+
+/// Adds the location to the component. \
+/// A `neutronic` tag is added to the last location of the component instead.
+fn push_location(
+    mut location: Location,
+    bytes_start: &BytesStart<'_>,
+    current_component: &mut Option<Component>,
+) -> anyhow::Result<()> {
+    let component = current_component.get_or_insert_default();
+
+    if bytes_start.name().as_ref() == b"neutronic" {
+        component
+            .location
+            .last_mut()
+            .context("neutronic is not inside of a location")?
+            .neutronic = Some(Box::new(location));
+
+        return Ok(());
+    }
+
+    if location.name.is_empty() {
+        location.name = component.type_name.clone();
+    }
+
+    component.location.push(location);
+
+    Ok(())
+}
+
+/// Adds the `rot`/`trans` tag to the last location of the component (or its neutronic location),
+/// inside of the open tags given by `path`. \
+/// Returns the index of the tag among its siblings.
+fn push_nested_transform(
+    nested_transform: NestedTransform,
+    path: &[usize],
+    in_neutronic: bool,
+    current_component: &mut Option<Component>,
+) -> anyhow::Result<usize> {
+    let mut location = current_component
+        .as_mut()
+        .and_then(|component| component.location.last_mut())
+        .context("rot/trans is not inside of a location")?;
+
+    if in_neutronic {
+        location = location
+            .neutronic
+            .as_deref_mut()
+            .context("rot/trans is not inside of the neutronic location")?;
+    }
+
+    Ok(location.push_nested(path, nested_transform))
+}
+
+/// Adds a `side-by-side-view-location` tag to the component or an `exclude`/`facing` tag
+/// to the last location of the component. \
+/// Returns `true` if the tag was one of these.
+fn push_location_child(
+    bytes_start: &BytesStart<'_>,
+    current_component: &mut Option<Component>,
+) -> anyhow::Result<bool> {
+    if bytes_start.name().as_ref() == b"side-by-side-view-location" {
+        let mut side_by_side_view_location = nalgebra::Point2::origin();
+
+        for attribute in bytes_start.attributes().flatten() {
+            match attribute.key.as_ref() {
+                b"x" => side_by_side_view_location.x = parse_attribute(&attribute.value)?,
+                b"y" => side_by_side_view_location.y = parse_attribute(&attribute.value)?,
+                _ => (),
+            }
+        }
+
+        current_component
+            .as_mut()
+            .context("side-by-side-view-location is not inside of a component")?
+            .side_by_side_view_location = Some(side_by_side_view_location);
+
+        return Ok(true);
+    }
+
+    if bytes_start.name().as_ref() == b"exclude" {
+        let sub_part = get_required_attribute(bytes_start, "sub-part")?;
+
+        current_component
+            .as_mut()
+            .and_then(|component| component.location.last_mut())
+            .context("exclude is not inside of a location")?
+            .exclude
+            .push(sub_part);
+
+        return Ok(true);
+    }
+
+    let mut facing = None;
+
+    Facing::try_match_bytes_start(&mut facing, bytes_start, None)
+        .context("could not parse facing from event")?;
+
+    if let Some(facing) = facing {
+        current_component
+            .as_mut()
+            .and_then(|component| component.location.last_mut())
+            .context("facing is not inside of a location")?
+            .facing = Some(facing);
+
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+/// Inserts the parameter into the innermost component link, component or type it is defined in.
+fn insert_parameter(
+    parameter: Parameter,
+    current_component_link: &mut Option<ComponentLink>,
+    current_component: &mut Option<Component>,
+    current_type: &mut Option<Type>,
+) -> anyhow::Result<()> {
+    let parameters = match (
+        current_component_link.as_mut(),
+        current_component.as_mut(),
+        current_type.as_mut(),
+    ) {
+        (Some(component_link), _, _) => &mut component_link.parameters,
+        (None, Some(component), _) => &mut component.parameters,
+        (None, None, Some(type_)) => &mut type_.parameters,
+        (None, None, None) => {
+            return Err(anyhow::anyhow!(
+                "parameter {} is not inside of a component-link, component or type",
+                parameter.name
+            ))
+        }
+    };
+
+    parameters.insert(parameter.name.clone(), parameter);
+
+    Ok(())
+}
+
+pub(crate) fn parameter_file_from_str(str: &str) -> anyhow::Result<ParameterFile> {
+    let mut reader = Reader::from_str(str);
+
+    reader.config_mut().trim_text(true);
+
+    let mut buf = Vec::new();
+
+    let mut parameter_file: Option<ParameterFile> = None;
+
+    let mut current_component_link: Option<ComponentLink> = None;
+
+    let mut current_parameter: Option<Parameter> = None;
+
+    loop {
+        let (bytes_start, is_empty) = match reader.read_event_into(&mut buf) {
+            Err(error) => {
+                return Err(anyhow::anyhow!(
+                    "Error at position{}: {:?}",
+                    reader.buffer_position(),
+                    error
+                ))
+            }
+            Ok(Event::Eof) => break,
+            Ok(Event::Start(bytes_start)) => (bytes_start, false),
+            Ok(Event::Empty(bytes_start)) => (bytes_start, true),
+            Ok(Event::End(bytes_end)) => {
+                match bytes_end.name().as_ref() {
+                    b"parameter" => {
+                        if let Some(parameter) = current_parameter.take() {
+                            insert_parameter(
+                                parameter,
+                                &mut current_component_link,
+                                &mut None,
+                                &mut None,
+                            )?;
+                        }
+                    }
+                    b"component-link" => {
+                        if let Some(component_link) = current_component_link.take() {
+                            parameter_file
+                                .as_mut()
+                                .context("component-link is not inside of a parameter-file")?
+                                .component_links
+                                .push(component_link);
+                        }
+                    }
+                    _ => (),
+                }
+
+                continue;
+            }
+            _ => continue,
+        };
+
+        if ParameterFile::try_match_bytes_start(&mut parameter_file, &bytes_start, None)
+            .context("could not parse parameter-file")?
+            .match_found()
+        {
+            continue;
+        }
+
+        if ComponentLink::try_match_bytes_start(&mut current_component_link, &bytes_start, None)
+            .context("could not parse component-link")?
+            .match_found()
+        {
+            continue;
+        }
+
+        Parameter::try_match_bytes_start(&mut current_parameter, &bytes_start, None)
+            .context("could not parse parameter")?;
+
+        // A self-closing `parameter` tag has no end event
+        if is_empty && bytes_start.name().as_ref() == b"parameter" {
+            if let Some(parameter) = current_parameter.take() {
+                insert_parameter(parameter, &mut current_component_link, &mut None, &mut None)?;
+            }
+        }
+    }
+
+    buf.clear();
+
+    parameter_file.context("could not find parameter-file tag")
+}
@@ -38,37 +38,37 @@ impl TryMatchAttribute for Translation {
         let p_suffix = add_suffix(b"p", suffix);
 
         match key {
-            val if val == &x_suffix => {
+            val if val == x_suffix => {
                 self_option
                     .get_or_insert(Translation::Cartesian(Point::default()))
                     .inner_mut()
                     .x = parse_attribute(value)?;
             }
-            val if val == &y_suffix => {
+            val if val == y_suffix => {
                 self_option
                     .get_or_insert(Translation::Cartesian(Point::default()))
                     .inner_mut()
                     .y = parse_attribute(value)?;
             }
-            val if val == &z_suffix => {
+            val if val == z_suffix => {
                 self_option
                     .get_or_insert(Translation::Cartesian(Point::default()))
                     .inner_mut()
                     .z = parse_attribute(value)?;
             }
-            val if val == &r_suffix => {
+            val if val == r_suffix => {
                 self_option
                     .get_or_insert(Translation::Spherical(Point::default()))
                     .inner_mut()
                     .x = parse_attribute(value)?;
             }
-            val if val == &t_suffix => {
+            val if val == t_suffix => {
                 self_option
                     .get_or_insert(Translation::Spherical(Point::default()))
                     .inner_mut()
                     .y = parse_attribute(value)?;
             }
-            val if val == &p_suffix => {
+            val if val == p_suffix => {
                 self_option
                     .get_or_insert(Translation::Spherical(Point::default()))
                     .inner_mut()
@@ -106,12 +106,10 @@ impl TryMatchAttribute for Rotation {
         let z_suffix = add_suffix(b"axis-z", suffix);
 
         match key {
-            val if val == &val_suffix || val == &rot_suffix => {
-                rotation.rot = parse_attribute(value)?
-            }
-            val if val == &x_suffix => rotation.axis.x = parse_attribute(value)?,
-            val if val == &y_suffix => rotation.axis.y = parse_attribute(value)?,
-            val if val == &z_suffix => rotation.axis.z = parse_attribute(value)?,
+            val if val == val_suffix || val == rot_suffix => rotation.rot = parse_attribute(value)?,
+            val if val == x_suffix => rotation.axis.x = parse_attribute(value)?,
+            val if val == y_suffix => rotation.axis.y = parse_attribute(value)?,
+            val if val == z_suffix => rotation.axis.z = parse_attribute(value)?,
             _ => {
                 response.match_found = false;
             }
@@ -202,6 +200,9 @@ impl TryMatchAttribute for Component {
             b"type" => {
                 component.type_name = std::str::from_utf8(&attribute.value)?.to_string();
             }
+            b"idlist" => {
+                component.idlist = Some(std::str::from_utf8(&attribute.value)?.to_string());
+            }
             b"" => {
                 response.match_found = false;
             }
@@ -236,6 +237,12 @@ impl TryMatchAttribute for IDEntry {
             b"end" => {
                 id_entry.end = parse_attribute(&attribute.value)?;
             }
+            b"step" => {
+                id_entry.step = parse_attribute(&attribute.value)?;
+            }
+            b"val" => {
+                *id_entry = IDEntry::single(parse_attribute(&attribute.value)?);
+            }
             _ => {
                 response.match_found = false;
             }
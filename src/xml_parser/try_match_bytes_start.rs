@@ -1,11 +1,20 @@
+use std::collections::BTreeMap;
+
 use anyhow::Context;
+use nalgebra::Point2;
 use quick_xml::events::BytesStart;
 
 use crate::{
+    defaults::Defaults,
     idlists::{IDEntry, IDList},
-    shapes::Hexahedron,
-    structs::{Component, Location, Locations, Response, Rotation, Translation, Type},
-    utils::parse_attribute,
+    parameter_file::{ComponentLink, ParameterFile},
+    parameters::{Formula, LogFile, LookupTable, Parameter, ParameterValue},
+    shapes::{BoundingBox, ShapeBuilder, Shapes},
+    structs::{
+        Component, Facing, Instrument, Location, Locations, NestedTransform, Response, Rotation,
+        Transform, Translation, Type,
+    },
+    utils::{parse_attribute, parse_timestamp},
     Point,
 };
 
@@ -19,7 +28,39 @@ pub trait TryMatchBytesStart: Sized {
     ) -> anyhow::Result<Response>;
 }
 
-impl TryMatchBytesStart for Hexahedron {
+/// Parses the attribute `key` of the tag if it is present.
+fn get_attribute<T>(bytes_start: &BytesStart<'_>, key: &str) -> anyhow::Result<Option<T>>
+where
+    T: std::str::FromStr,
+    <T as std::str::FromStr>::Err: Into<anyhow::Error>,
+{
+    match bytes_start.try_get_attribute(key)? {
+        Some(attribute) => Ok(Some(parse_attribute(&attribute.value).context(format!(
+            "could not parse attribute {:?} of tag {:?}",
+            key,
+            String::from_utf8_lossy(bytes_start.name().as_ref())
+        ))?)),
+        None => Ok(None),
+    }
+}
+
+/// Parses the attribute `key` of the tag and errors if it is missing.
+pub(crate) fn get_required_attribute<T>(
+    bytes_start: &BytesStart<'_>,
+    key: &str,
+) -> anyhow::Result<T>
+where
+    T: std::str::FromStr,
+    <T as std::str::FromStr>::Err: Into<anyhow::Error>,
+{
+    get_attribute(bytes_start, key)?.context(format!(
+        "could not get attribute {:?} of tag {:?}",
+        key,
+        String::from_utf8_lossy(bytes_start.name().as_ref())
+    ))
+}
+
+impl TryMatchBytesStart for Instrument {
     fn try_match_bytes_start(
         self_option: &mut Option<Self>,
         bytes_start: &BytesStart<'_>,
@@ -27,39 +68,151 @@ impl TryMatchBytesStart for Hexahedron {
     ) -> anyhow::Result<Response> {
         let mut response = Response::default();
 
-        let hexahedron = self_option.get_or_insert(Hexahedron::default());
+        if bytes_start.name().as_ref() != b"instrument" {
+            return Ok(response);
+        }
 
-        if bytes_start.name().as_ref() == b"hexahedron" {
-            response.match_found = true;
+        response.match_found = true;
 
-            match bytes_start.try_get_attribute(b"id")? {
-                Some(attribute) => {
-                    hexahedron.id = parse_attribute(&attribute.value)?;
+        let mut instrument = Instrument::default();
+
+        for attribute in bytes_start.attributes().flatten() {
+            let value = std::str::from_utf8(&attribute.value)?;
+
+            match attribute.key.as_ref() {
+                b"name" => instrument.name = value.to_string(),
+                b"valid-from" => instrument.valid_from = Some(parse_timestamp(value)?),
+                b"valid-to" => instrument.valid_to = Some(parse_timestamp(value)?),
+                b"last-modified" => instrument.last_modified = Some(parse_timestamp(value)?),
+                // The namespace prefix (usually `xsi`) is not fixed
+                _ if attribute.key.local_name().as_ref() == b"schemaLocation" => {
+                    instrument.schema_location = Some(value.to_string())
                 }
-                None => (), // TODO: error if no id attribute?
+                _ => (),
             }
+        }
+
+        *self_option = Some(instrument);
+
+        Ok(response)
+    }
+}
+
+impl TryMatchBytesStart for Defaults {
+    fn try_match_bytes_start(
+        self_option: &mut Option<Self>,
+        bytes_start: &BytesStart<'_>,
+        _suffix: Option<&str>,
+    ) -> anyhow::Result<Response> {
+        let mut response = Response::default();
+
+        if bytes_start.name().as_ref() == b"defaults" {
+            response.match_found = true;
+
+            self_option.get_or_insert_default();
 
             return Ok(response);
         }
 
-        match hexahedron.get_mut(bytes_start.name().as_ref()) {
-            Some(current_point) => {
-                response.match_found = true;
+        // The tags below are only valid inside of the `defaults` tag
+        let Some(defaults) = self_option.as_mut() else {
+            return Ok(response);
+        };
 
-                let mut point = None;
+        response.match_found = true;
 
-                for attribute in bytes_start.attributes() {
-                    if let Ok(attribute) = attribute {
-                        Point::try_match_attribute(&mut point, &attribute, None)?;
-                    }
+        match bytes_start.name().as_ref() {
+            b"length" => defaults.length_unit = get_required_attribute(bytes_start, "unit")?,
+            b"angle" => defaults.angle_unit = get_required_attribute(bytes_start, "unit")?,
+            b"reference-frame" => (),
+            b"along-beam" => {
+                defaults.reference_frame.along_beam = get_required_attribute(bytes_start, "axis")?
+            }
+            b"pointing-up" => {
+                defaults.reference_frame.pointing_up = get_required_attribute(bytes_start, "axis")?
+            }
+            b"handedness" => {
+                defaults.reference_frame.handedness = get_required_attribute(bytes_start, "val")?
+            }
+            b"theta-sign" => {
+                defaults.reference_frame.theta_sign =
+                    Some(get_required_attribute(bytes_start, "axis")?)
+            }
+            b"default-view" => {
+                let default_view = defaults.default_view.get_or_insert_default();
+
+                if let Some(view) = get_attribute(bytes_start, "view")? {
+                    default_view.view = view;
                 }
 
-                *current_point = point.unwrap_or_default(); // TODO: error if point is None?
+                if let Some(axis_view) = get_attribute(bytes_start, "axis-view")? {
+                    default_view.axis_view = axis_view;
+                }
+            }
+            b"components-are-facing" => {
+                let mut translation = None;
 
-                return Ok(response);
+                for attribute in bytes_start.attributes().flatten() {
+                    Translation::try_match_attribute(&mut translation, &attribute, None)?;
+                }
+
+                defaults.components_are_facing =
+                    Some(translation.unwrap_or(Translation::Cartesian(Point::origin())));
+            }
+            b"offsets" => {
+                if let Some(spherical) = get_attribute(bytes_start, "spherical")? {
+                    defaults.offsets.spherical = spherical;
+                }
             }
-            None => return Ok(response),
+            _ => response.match_found = false,
         }
+
+        Ok(response)
+    }
+}
+
+impl TryMatchBytesStart for ShapeBuilder {
+    fn try_match_bytes_start(
+        self_option: &mut Option<Self>,
+        bytes_start: &BytesStart<'_>,
+        _suffix: Option<&str>,
+    ) -> anyhow::Result<Response> {
+        let mut response = Response::default();
+
+        let tag = String::from_utf8_lossy(bytes_start.name().as_ref()).to_string();
+
+        if Shapes::TAGS.contains(&tag.as_str()) {
+            response.match_found = true;
+
+            *self_option = Some(ShapeBuilder {
+                // TODO: error if no id attribute?
+                id: get_attribute(bytes_start, "id")?.unwrap_or_default(),
+                tag,
+                ..Default::default()
+            });
+
+            return Ok(response);
+        }
+
+        // The tags below are only valid inside of a shape tag
+        let Some(shape_builder) = self_option.as_mut() else {
+            return Ok(response);
+        };
+
+        response.match_found = true;
+
+        let mut attributes = BTreeMap::new();
+
+        for attribute in bytes_start.attributes().flatten() {
+            attributes.insert(
+                String::from_utf8_lossy(attribute.key.as_ref()).to_string(),
+                String::from_utf8_lossy(&attribute.value).to_string(),
+            );
+        }
+
+        shape_builder.elements.insert(tag, attributes);
+
+        Ok(response)
     }
 }
 
@@ -71,82 +224,65 @@ impl TryMatchBytesStart for Locations {
     ) -> anyhow::Result<Response> {
         let mut response = Response::default();
 
-        match bytes_start.name().as_ref() {
-            b"locations" => {
-                response.match_found = true;
+        if bytes_start.name().as_ref() == b"locations" {
+            response.match_found = true;
+
+            let locations = self_option.get_or_insert(Locations::default());
 
-                let locations = self_option.get_or_insert(Locations::default());
-
-                let mut start_translation: Option<Translation> = None;
-                let mut end_translation: Option<Translation> = None;
-
-                let mut start_rotation: Option<Rotation> = None;
-                let mut end_rotation: Option<Rotation> = None;
-
-                for attribute in bytes_start.attributes() {
-                    if let Ok(attribute) = attribute {
-                        let key = attribute.key.as_ref();
-
-                        match key {
-                            b"n-elements" => {
-                                locations.n_elements = parse_attribute(&attribute.value)?;
-                                continue;
-                            }
-                            b"name" => {
-                                locations.name = std::str::from_utf8(&attribute.value)?.to_string();
-                                continue;
-                            }
-                            b"name-count-start" => {
-                                locations.name_count_start = parse_attribute(&attribute.value)?;
-                                continue;
-                            }
-                            _ => (),
-                        }
-
-                        if Translation::try_match_attribute(
-                            &mut start_translation,
-                            &attribute,
-                            None,
-                        )?
-                        .match_found()
-                        {
-                            continue;
-                        }
-
-                        if Translation::try_match_attribute(
-                            &mut end_translation,
-                            &attribute,
-                            Some("-end"),
-                        )?
-                        .match_found()
-                        {
-                            continue;
-                        }
-
-                        if Rotation::try_match_attribute(&mut start_rotation, &attribute, None)?
-                            .match_found()
-                        {
-                            continue;
-                        }
-
-                        if Rotation::try_match_attribute(
-                            &mut end_rotation,
-                            &attribute,
-                            Some("-end"),
-                        )?
-                        .match_found()
-                        {
-                            continue;
-                        }
+            let mut start_translation: Option<Translation> = None;
+            let mut end_translation: Option<Translation> = None;
+
+            let mut start_rotation: Option<Rotation> = None;
+            let mut end_rotation: Option<Rotation> = None;
+
+            for attribute in bytes_start.attributes().flatten() {
+                let key = attribute.key.as_ref();
+
+                match key {
+                    b"n-elements" => {
+                        locations.n_elements = parse_attribute(&attribute.value)?;
+                        continue;
+                    }
+                    b"name" => {
+                        locations.name = std::str::from_utf8(&attribute.value)?.to_string();
+                        continue;
                     }
+                    b"name-count-start" => {
+                        locations.name_count_start = parse_attribute(&attribute.value)?;
+                        continue;
+                    }
+                    _ => (),
+                }
+
+                if Translation::try_match_attribute(&mut start_translation, &attribute, None)?
+                    .match_found()
+                {
+                    continue;
+                }
+
+                if Translation::try_match_attribute(&mut end_translation, &attribute, Some("-end"))?
+                    .match_found()
+                {
+                    continue;
+                }
+
+                if Rotation::try_match_attribute(&mut start_rotation, &attribute, None)?
+                    .match_found()
+                {
+                    continue;
                 }
 
-                locations.start_translation = start_translation;
-                locations.end_translation = end_translation;
-                locations.start_rotation = start_rotation;
-                locations.end_rotation = end_rotation;
+                if Rotation::try_match_attribute(&mut end_rotation, &attribute, Some("-end"))?
+                    .match_found()
+                {
+                    continue;
+                }
             }
-            _ => (),
+
+            locations.start_translation = start_translation;
+            locations.end_translation = end_translation;
+            locations.start_rotation = start_rotation;
+            locations.end_rotation = end_rotation;
         }
 
         Ok(response)
@@ -161,19 +297,96 @@ impl TryMatchBytesStart for Rotation {
     ) -> anyhow::Result<Response> {
         let mut response = Response::default();
 
-        match bytes_start.name().as_ref() {
+        if bytes_start.name().as_ref() == b"rot" {
+            response.match_found = true;
+
+            for attribute in bytes_start.attributes().flatten() {
+                Rotation::try_match_attribute(self_option, &attribute, None)?;
+            }
+        }
+
+        Ok(response)
+    }
+}
+
+impl TryMatchBytesStart for NestedTransform {
+    fn try_match_bytes_start(
+        self_option: &mut Option<Self>,
+        bytes_start: &BytesStart<'_>,
+        _suffix: Option<&str>,
+    ) -> anyhow::Result<Response> {
+        let mut response = Response::default();
+
+        let transform = match bytes_start.name().as_ref() {
             b"rot" => {
-                response.match_found = true;
+                let mut rotation = None;
 
-                for attribute in bytes_start.attributes() {
-                    if let Ok(attribute) = attribute {
-                        Rotation::try_match_attribute(self_option, &attribute, None)?;
-                    }
+                Rotation::try_match_bytes_start(&mut rotation, bytes_start, None)?;
+
+                // A `rot` tag without attributes is a rotation by zero
+                Transform::Rotation(rotation.unwrap_or_default())
+            }
+            b"trans" => {
+                let mut translation = None;
+
+                for attribute in bytes_start.attributes().flatten() {
+                    Translation::try_match_attribute(&mut translation, &attribute, None)?;
                 }
+
+                Transform::Translation(
+                    translation.unwrap_or(Translation::Cartesian(Point::origin())),
+                )
             }
-            _ => (),
+            _ => return Ok(response),
+        };
+
+        response.match_found = true;
+
+        *self_option = Some(NestedTransform {
+            transform,
+            children: Vec::new(),
+        });
+
+        Ok(response)
+    }
+}
+
+impl TryMatchBytesStart for Facing {
+    fn try_match_bytes_start(
+        self_option: &mut Option<Self>,
+        bytes_start: &BytesStart<'_>,
+        _suffix: Option<&str>,
+    ) -> anyhow::Result<Response> {
+        let mut response = Response::default();
+
+        if bytes_start.name().as_ref() != b"facing" {
+            return Ok(response);
+        }
+
+        response.match_found = true;
+
+        let mut point = None;
+
+        for attribute in bytes_start.attributes().flatten() {
+            Translation::try_match_attribute(&mut point, &attribute, None)?;
         }
 
+        let point = match get_attribute::<String>(bytes_start, "val")? {
+            Some(val) if val == "none" => None,
+            Some(val) => {
+                return Err(anyhow::anyhow!(
+                    "could not parse val {:?} of facing tag, expected \"none\"",
+                    val
+                ))
+            }
+            None => Some(point.unwrap_or(Translation::Cartesian(Point::origin()))),
+        };
+
+        *self_option = Some(Facing {
+            point,
+            rot: get_attribute(bytes_start, "rot")?,
+        });
+
         Ok(response)
     }
 }
@@ -186,51 +399,53 @@ impl TryMatchBytesStart for Location {
     ) -> anyhow::Result<Response> {
         let mut response = Response::default();
 
-        match bytes_start.name().as_ref() {
-            b"location" => {
-                response.match_found = true;
+        // The `neutronic` tag has the same attributes as the `location` tag
+        if matches!(bytes_start.name().as_ref(), b"location" | b"neutronic") {
+            response.match_found = true;
 
-                let location = self_option.get_or_insert(Location::default());
+            let location = self_option.get_or_insert(Location::default());
 
-                let mut translation: Option<Translation> = None;
+            parse_location_attributes(location, bytes_start)?;
+        }
 
-                let mut rotation: Option<Rotation> = None;
+        Ok(response)
+    }
+}
 
-                for attribute in bytes_start.attributes() {
-                    if let Ok(attribute) = attribute {
-                        let key = attribute.key.as_ref();
+/// Parses the name, translation and rotation attributes of a location-like tag.
+fn parse_location_attributes(
+    location: &mut Location,
+    bytes_start: &BytesStart<'_>,
+) -> anyhow::Result<()> {
+    let mut translation: Option<Translation> = None;
 
-                        if key == b"name" {
-                            location.name = std::str::from_utf8(&attribute.value)?.to_string();
-                        }
+    let mut rotation: Option<Rotation> = None;
 
-                        if Translation::try_match_attribute(&mut translation, &attribute, None)?
-                            .match_found()
-                        {
-                            continue;
-                        }
+    for attribute in bytes_start.attributes().flatten() {
+        let key = attribute.key.as_ref();
 
-                        if Rotation::try_match_attribute(&mut rotation, &attribute, None)?
-                            .match_found()
-                        {
-                            continue;
-                        }
-                    }
-                }
+        if key == b"name" {
+            location.name = std::str::from_utf8(&attribute.value)?.to_string();
+        }
 
-                if let Some(translation) = translation {
-                    location.translation.push(translation);
-                }
+        if Translation::try_match_attribute(&mut translation, &attribute, None)?.match_found() {
+            continue;
+        }
 
-                if let Some(rotation) = rotation {
-                    location.rotation.push(rotation);
-                }
-            }
-            _ => (),
+        if Rotation::try_match_attribute(&mut rotation, &attribute, None)?.match_found() {
+            continue;
         }
+    }
 
-        Ok(response)
+    if let Some(translation) = translation {
+        location.translation.push(translation);
+    }
+
+    if let Some(rotation) = rotation {
+        location.rotation.push(rotation);
     }
+
+    Ok(())
 }
 
 impl TryMatchBytesStart for Type {
@@ -244,18 +459,45 @@ impl TryMatchBytesStart for Type {
         if bytes_start.name().as_ref() == b"type" {
             response.match_found = true;
 
-            for attribute in bytes_start.attributes() {
-                if let Ok(attribute) = attribute {
-                    Type::try_match_attribute(self_option, &attribute, None)?;
-                }
+            for attribute in bytes_start.attributes().flatten() {
+                Type::try_match_attribute(self_option, &attribute, None)?;
             }
+
+            return Ok(response);
+        }
+
+        // The tags below are only valid inside of the `type` tag
+        let Some(type_) = self_option.as_mut() else {
+            return Ok(response);
+        };
+
+        response.match_found = true;
+
+        match bytes_start.name().as_ref() {
+            b"combine-components-into-one-shape" => {
+                type_.combine_components_into_one_shape = true;
+            }
+            b"translate-rotate-combined-shape-to" => {
+                let location = type_
+                    .translate_rotate_combined_shape_to
+                    .get_or_insert_default();
+
+                parse_location_attributes(location, bytes_start)?;
+            }
+            b"vertex" => {
+                type_.vertices.push(Point2::new(
+                    get_required_attribute(bytes_start, "x")?,
+                    get_required_attribute(bytes_start, "y")?,
+                ));
+            }
+            _ => response.match_found = false,
         }
 
         Ok(response)
     }
 }
 
-impl TryMatchBytesStart for Component {
+impl TryMatchBytesStart for BoundingBox {
     fn try_match_bytes_start(
         self_option: &mut Option<Self>,
         bytes_start: &BytesStart<'_>,
@@ -263,20 +505,47 @@ impl TryMatchBytesStart for Component {
     ) -> anyhow::Result<Response> {
         let mut response = Response::default();
 
-        match bytes_start.name().as_ref() {
-            b"component" => {
-                response.match_found = true;
+        if bytes_start.name().as_ref() == b"bounding-box" {
+            response.match_found = true;
 
-                let component = self_option.get_or_insert(Component::default());
+            self_option.get_or_insert_default();
 
-                let type_ = bytes_start
-                    .try_get_attribute("type")?
-                    .context("could not get attribute type")?
-                    .value;
+            return Ok(response);
+        }
+
+        // The tags below are only valid inside of the `bounding-box` tag
+        let Some(bounding_box) = self_option.as_mut() else {
+            return Ok(response);
+        };
+
+        if let Some(value) = bounding_box.get_mut(bytes_start.name().as_ref()) {
+            response.match_found = true;
+
+            *value = get_required_attribute(bytes_start, "val")?;
+        }
+
+        Ok(response)
+    }
+}
+
+impl TryMatchBytesStart for Component {
+    fn try_match_bytes_start(
+        self_option: &mut Option<Self>,
+        bytes_start: &BytesStart<'_>,
+        _suffix: Option<&str>,
+    ) -> anyhow::Result<Response> {
+        let mut response = Response::default();
 
-                component.type_name = std::str::from_utf8(&type_)?.to_string();
+        if bytes_start.name().as_ref() == b"component" {
+            response.match_found = true;
+
+            bytes_start
+                .try_get_attribute("type")?
+                .context("could not get attribute type")?;
+
+            for attribute in bytes_start.attributes().flatten() {
+                Component::try_match_attribute(self_option, &attribute, None)?;
             }
-            _ => (),
         }
 
         Ok(response)
@@ -311,17 +580,31 @@ impl TryMatchBytesStart for IDList {
                     .as_mut()
                     .context("Can't have an `id` tag outside of an `idlist` tag")?;
 
-                let mut id_entry = None;
+                // Either a single id with `val` or a range with `start` and `end`
+                let is_single = bytes_start.try_get_attribute("val")?.is_some();
+                let is_range = bytes_start.try_get_attribute("start")?.is_some()
+                    && bytes_start.try_get_attribute("end")?.is_some();
 
-                for attribute in bytes_start.attributes() {
-                    if let Ok(attribute) = attribute {
-                        IDEntry::try_match_attribute(&mut id_entry, &attribute, None)?;
-                    }
+                if is_single == is_range {
+                    return Err(anyhow::anyhow!(
+                        "`id` tag in idlist {} needs either `val` or `start` and `end`",
+                        id_list.name
+                    ));
                 }
 
-                if let Some(id_entry) = id_entry {
-                    id_list.entries.push(id_entry);
+                let mut id_entry = None;
+
+                for attribute in bytes_start.attributes().flatten() {
+                    IDEntry::try_match_attribute(&mut id_entry, &attribute, None)?;
                 }
+
+                let id_entry = id_entry.unwrap_or_default();
+
+                id_entry
+                    .validate()
+                    .context(format!("invalid `id` tag in idlist {}", id_list.name))?;
+
+                id_list.entries.push(id_entry);
             }
             _ => (),
         }
@@ -329,3 +612,138 @@ impl TryMatchBytesStart for IDList {
         Ok(response)
     }
 }
+
+impl TryMatchBytesStart for Parameter {
+    fn try_match_bytes_start(
+        self_option: &mut Option<Self>,
+        bytes_start: &BytesStart<'_>,
+        _suffix: Option<&str>,
+    ) -> anyhow::Result<Response> {
+        let mut response = Response::default();
+
+        if bytes_start.name().as_ref() == b"parameter" {
+            response.match_found = true;
+
+            let parameter = self_option.get_or_insert_default();
+
+            parameter.name = get_required_attribute(bytes_start, "name")?;
+
+            if let Some(parameter_type) = get_attribute(bytes_start, "type")? {
+                parameter.parameter_type = parameter_type;
+            }
+
+            if let Some(visible) = get_attribute(bytes_start, "visible")? {
+                parameter.visible = visible;
+            }
+
+            parameter.unit = get_attribute(bytes_start, "unit")?;
+
+            return Ok(response);
+        }
+
+        // The tags below are only valid inside of the `parameter` tag
+        let Some(parameter) = self_option.as_mut() else {
+            return Ok(response);
+        };
+
+        response.match_found = true;
+
+        match bytes_start.name().as_ref() {
+            b"value" => {
+                parameter.value = Some(ParameterValue::Value(get_required_attribute(
+                    bytes_start,
+                    "val",
+                )?));
+
+                if let Some(unit) = get_attribute(bytes_start, "unit")? {
+                    parameter.unit = Some(unit);
+                }
+            }
+            b"logfile" => {
+                parameter.value = Some(ParameterValue::LogFile(LogFile {
+                    id: get_required_attribute(bytes_start, "id")?,
+                    eq: get_attribute(bytes_start, "eq")?,
+                    extract_single_value_as: get_attribute(bytes_start, "extract-single-value-as")?
+                        .unwrap_or_default(),
+                }));
+            }
+            b"formula" => {
+                parameter.value = Some(ParameterValue::Formula(Formula {
+                    eq: get_required_attribute(bytes_start, "eq")?,
+                    unit: get_attribute(bytes_start, "unit")?,
+                    result_unit: get_attribute(bytes_start, "result-unit")?,
+                }));
+            }
+            b"lookuptable" => {
+                parameter.value = Some(ParameterValue::LookupTable(LookupTable {
+                    interpolation: get_attribute(bytes_start, "interpolation")?.unwrap_or_default(),
+                    x_unit: get_attribute(bytes_start, "x-unit")?,
+                    y_unit: get_attribute(bytes_start, "y-unit")?,
+                    points: Vec::new(),
+                }));
+            }
+            b"point" => {
+                let Some(ParameterValue::LookupTable(lookup_table)) = parameter.value.as_mut()
+                else {
+                    return Err(anyhow::anyhow!(
+                        "point of parameter {} is not inside of a lookuptable",
+                        parameter.name
+                    ));
+                };
+
+                lookup_table.points.push((
+                    get_required_attribute(bytes_start, "x")?,
+                    get_required_attribute(bytes_start, "y")?,
+                ));
+            }
+            b"description" => {
+                parameter.description = Some(get_required_attribute(bytes_start, "is")?);
+            }
+            _ => response.match_found = false,
+        }
+
+        Ok(response)
+    }
+}
+
+impl TryMatchBytesStart for ParameterFile {
+    fn try_match_bytes_start(
+        self_option: &mut Option<Self>,
+        bytes_start: &BytesStart<'_>,
+        _suffix: Option<&str>,
+    ) -> anyhow::Result<Response> {
+        let mut response = Response::default();
+
+        if bytes_start.name().as_ref() == b"parameter-file" {
+            response.match_found = true;
+
+            let parameter_file = self_option.get_or_insert_default();
+
+            parameter_file.instrument =
+                get_attribute(bytes_start, "instrument")?.unwrap_or_default();
+            parameter_file.valid_from = get_attribute(bytes_start, "valid-from")?;
+        }
+
+        Ok(response)
+    }
+}
+
+impl TryMatchBytesStart for ComponentLink {
+    fn try_match_bytes_start(
+        self_option: &mut Option<Self>,
+        bytes_start: &BytesStart<'_>,
+        _suffix: Option<&str>,
+    ) -> anyhow::Result<Response> {
+        let mut response = Response::default();
+
+        if bytes_start.name().as_ref() == b"component-link" {
+            response.match_found = true;
+
+            let component_link = self_option.get_or_insert_default();
+
+            component_link.name = get_required_attribute(bytes_start, "name")?;
+        }
+
+        Ok(response)
+    }
+}
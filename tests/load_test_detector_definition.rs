@@ -11,14 +11,1950 @@ mod test_detector_definition {
         let detector_definition = mantid_idf::DetectorDefinition::from_str(&content)
             .expect("could not parse detector definition");
 
-        assert!(detector_definition.component_tree.children.len() > 0);
+        assert!(!detector_definition.component_tree.children.is_empty());
         assert!(detector_definition.component_tree.component.is_root());
 
+        let type_names = detector_definition
+            .component_tree
+            .children
+            .iter()
+            .map(|child| child.component.type_name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(type_names, ["moderator", "sample-position", "VoxelsRoot"]);
+    }
+
+    #[test]
+    fn test_top_level_components_with_same_type() {
+        let content = r#"
+            <instrument name="Monitors">
+                <component type="monitor">
+                    <location z="-1.0" name="upstream"/>
+                </component>
+                <component type="sample-position">
+                    <location/>
+                </component>
+                <component type="monitor">
+                    <location z="1.0" name="downstream"/>
+                </component>
+                <type is="monitor" name="monitor"/>
+                <type is="SamplePos" name="sample-position"/>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let children = &detector_definition.component_tree.children;
+
+        assert_eq!(children.len(), 3);
+        assert_eq!(children[0].component.location[0].name, "upstream");
+        assert_eq!(children[1].component.type_name, "sample-position");
+        assert_eq!(children[2].component.location[0].name, "downstream");
+    }
+
+    #[test]
+    fn test_defaults() {
+        use mantid_idf::defaults::{AngleUnit, Handedness, LengthUnit};
+        use mantid_idf::utils::Axes;
+
+        let content =
+            std::fs::read_to_string(TEST_DETECTOR_DEFINITION_PATH).expect("could not read file");
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(&content)
+            .expect("could not parse detector definition");
+
+        let defaults = &detector_definition.defaults;
+
+        assert_eq!(defaults.length_unit, LengthUnit::Metre);
+        assert_eq!(defaults.angle_unit, AngleUnit::Degree);
+        assert_eq!(defaults.reference_frame.along_beam, Axes::Z);
+        assert_eq!(defaults.reference_frame.pointing_up, Axes::Y);
+        assert_eq!(defaults.reference_frame.handedness, Handedness::Right);
+        assert!(defaults.default_view.is_none());
+        assert!(defaults.components_are_facing.is_none());
+    }
+
+    #[test]
+    fn test_non_default_defaults() {
+        use mantid_idf::defaults::{AngleUnit, Handedness, LengthUnit, SphericalOffsets, View};
+        use mantid_idf::structs::Translation;
+        use mantid_idf::utils::Axes;
+        use mantid_idf::Point;
+
+        let content = r#"
+            <instrument name="Defaults">
+                <defaults>
+                    <length unit="mm"/>
+                    <angle unit="radian"/>
+                    <reference-frame>
+                        <along-beam axis="x"/>
+                        <pointing-up axis="z"/>
+                        <handedness val="left"/>
+                        <theta-sign axis="y"/>
+                    </reference-frame>
+                    <default-view view="spherical_y" axis-view="y-"/>
+                    <components-are-facing x="1" y="2" z="3"/>
+                    <offsets spherical="delta"/>
+                </defaults>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let defaults = &detector_definition.defaults;
+
+        assert_eq!(defaults.length_unit, LengthUnit::Millimetre);
+        assert_eq!(defaults.angle_unit, AngleUnit::Radian);
+        assert!((defaults.length_to_metres(1500.0) - 1.5).abs() < 1e-6);
+        assert_eq!(defaults.angle_to_radians(0.5), 0.5);
+
+        assert_eq!(defaults.reference_frame.along_beam, Axes::X);
+        assert_eq!(defaults.reference_frame.pointing_up, Axes::Z);
+        assert_eq!(defaults.reference_frame.handedness, Handedness::Left);
+        assert_eq!(defaults.reference_frame.theta_sign, Some(Axes::Y));
+        assert_eq!(defaults.reference_frame.theta_sign_axis(), Axes::Y);
+
+        let default_view = defaults.default_view.as_ref().expect("default view is set");
+
+        assert_eq!(default_view.view, View::SphericalY);
+        assert_eq!(default_view.axis_view.axis, Axes::Y);
+        assert!(!default_view.axis_view.positive);
+
+        assert!(matches!(
+            defaults.components_are_facing,
+            Some(Translation::Cartesian(point)) if point == Point::new(1.0, 2.0, 3.0)
+        ));
+        assert_eq!(defaults.offsets.spherical, SphericalOffsets::Delta);
+
+        // Unknown values are an error
+        for (valid, invalid) in [
+            (r#"<length unit="mm"/>"#, r#"<length unit="inch"/>"#),
+            (r#"<angle unit="radian"/>"#, r#"<angle unit="gradian"/>"#),
+            (r#"<along-beam axis="x"/>"#, r#"<along-beam axis="w"/>"#),
+            (
+                r#"<offsets spherical="delta"/>"#,
+                r#"<offsets spherical="relative"/>"#,
+            ),
+        ] {
+            let content = content.replace(valid, invalid);
+
+            assert!(mantid_idf::DetectorDefinition::from_str(&content).is_err());
+        }
+    }
+
+    #[test]
+    fn test_inline_parameters() {
+        use mantid_idf::parameters::ParameterType;
+
+        let content = r#"
+            <instrument name="Parameters">
+                <component type="tube">
+                    <location/>
+                    <parameter name="TubePressure">
+                        <value val="10.0"/>
+                    </parameter>
+                </component>
+                <type name="tube" is="detector">
+                    <parameter name="Efixed" type="double" unit="meV">
+                        <value val="3.5"/>
+                        <description is="Fixed final energy"/>
+                    </parameter>
+                    <parameter name="Gas" type="string">
+                        <value val="He3"/>
+                    </parameter>
+                </type>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let component = &detector_definition.component_tree.children[0].component;
+
+        let tube_pressure = &component.parameters["TubePressure"];
+
+        assert_eq!(tube_pressure.get_value::<f64>().unwrap(), 10.0);
+
+        let type_ = &detector_definition.types["tube"];
+
+        let efixed = &type_.parameters["Efixed"];
+
+        assert_eq!(efixed.get_value::<f32>().unwrap(), 3.5);
+        assert_eq!(efixed.unit.as_deref(), Some("meV"));
+        assert_eq!(efixed.description.as_deref(), Some("Fixed final energy"));
+
+        let gas = &type_.parameters["Gas"];
+
+        assert_eq!(gas.parameter_type, ParameterType::String);
+        assert_eq!(gas.get_value::<String>().unwrap(), "He3");
+        assert!(gas.get_value::<f64>().is_err());
+    }
+
+    #[test]
+    fn test_parameter_file() {
+        use mantid_idf::parameter_file::ParameterFile;
+
+        let content =
+            std::fs::read_to_string(TEST_DETECTOR_DEFINITION_PATH).expect("could not read file");
+
+        let mut detector_definition = mantid_idf::DetectorDefinition::from_str(&content)
+            .expect("could not parse detector definition");
+
+        let parameter_file = ParameterFile::from_str(
+            r#"
+            <parameter-file instrument="Test" valid-from="2024-10-18 00:00:01">
+                <component-link name="Test">
+                    <parameter name="deltaE-mode" type="string">
+                        <value val="direct"/>
+                    </parameter>
+                </component-link>
+                <component-link name="X03/X00A">
+                    <parameter name="TubePressure">
+                        <value val="10.0"/>
+                    </parameter>
+                </component-link>
+                <component-link name="Y0B4">
+                    <parameter name="DeadTime">
+                        <value val="0.5"/>
+                    </parameter>
+                </component-link>
+                <component-link name="X00A/Y0B4">
+                    <parameter name="DeadTime">
+                        <value val="0.5"/>
+                    </parameter>
+                </component-link>
+            </parameter-file>
+            "#,
+        )
+        .expect("could not parse parameter file");
+
+        let unmatched = detector_definition.apply_parameter_file(&parameter_file);
+
+        assert_eq!(unmatched, ["X00A/Y0B4"]);
+
+        let root = &detector_definition.component_tree;
+
+        assert!(root.component.parameters.contains_key("deltaE-mode"));
+
+        let x00a = root.get_nodes_by_name("X00A");
+
+        assert_eq!(x00a.len(), 1);
+        assert_eq!(
+            x00a[0].component.parameters["TubePressure"]
+                .get_value::<f64>()
+                .unwrap(),
+            10.0
+        );
+
+        let y0b4 = root.get_nodes_by_name("X00B/Y0B4");
+
+        assert_eq!(y0b4.len(), 1);
+        assert!(y0b4[0].component.parameters.contains_key("DeadTime"));
+
+        // Parameters are inherited from the parents
+        let y0a0 = root.get_nodes_by_name("Y0A0")[0];
+
+        assert_eq!(
+            y0a0.get_parameter::<f64>("TubePressure").unwrap(),
+            Some(10.0)
+        );
+        assert_eq!(
+            y0a0.get_local_parameter::<f64>("TubePressure").unwrap(),
+            None
+        );
         assert_eq!(
-            detector_definition.component_tree.children[0]
+            y0a0.get_parameter::<String>("deltaE-mode")
+                .unwrap()
+                .as_deref(),
+            Some("direct")
+        );
+
+        let y0b0 = root.get_nodes_by_name("Y0B0")[0];
+
+        assert_eq!(y0b0.get_parameter::<f64>("TubePressure").unwrap(), None);
+    }
+
+    #[test]
+    fn test_location_component_links() {
+        use mantid_idf::parameter_file::ParameterFile;
+
+        let content = r#"
+            <instrument name="Test">
+                <component type="bank">
+                    <location name="bank1"/>
+                    <location name="bank2" z="1"/>
+                </component>
+                <type name="bank">
+                    <component type="tube">
+                        <location name="tube1" x="1"/>
+                        <location name="tube2" x="2"/>
+                        <location name="tube3" x="3"/>
+                        <location x="4"/>
+                    </component>
+                </type>
+                <type name="tube" is="detector"/>
+            </instrument>
+        "#;
+
+        let mut detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let parameter_file = ParameterFile::from_str(
+            r#"
+            <parameter-file instrument="Test">
+                <component-link name="bank1/tube3">
+                    <parameter name="DeadTime">
+                        <value val="0.5"/>
+                    </parameter>
+                </component-link>
+                <component-link name="tube">
+                    <parameter name="TubePressure">
+                        <value val="10.0"/>
+                    </parameter>
+                </component-link>
+                <component-link name="bank">
+                    <parameter name="Efixed">
+                        <value val="3.5"/>
+                    </parameter>
+                </component-link>
+            </parameter-file>
+            "#,
+        )
+        .expect("could not parse parameter file");
+
+        let unmatched = detector_definition.apply_parameter_file(&parameter_file);
+
+        // The type name is hidden by the names of the banks
+        assert_eq!(unmatched, ["bank"]);
+
+        let tree = &detector_definition.component_tree;
+
+        let tube = tree.get_nodes_by_name("tube3")[0];
+
+        assert!(!tube.component.parameters.contains_key("DeadTime"));
+
+        // Only the third tube receives the parameter of the link
+        let dead_times = (0..4)
+            .map(|location| tube.get_location_parameter::<f64>("DeadTime", location))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(dead_times, [None, None, Some(0.5), None]);
+
+        // The type name only matches the unnamed location
+        assert_eq!(
+            tube.get_location_parameter::<f64>("TubePressure", 3)
+                .unwrap(),
+            Some(10.0)
+        );
+        assert_eq!(
+            tube.get_location_parameter::<f64>("TubePressure", 0)
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_unmatched_component_links() {
+        let content = r#"
+            <instrument name="Test">
+                <component type="tube"><location/></component>
+                <type name="tube" is="detector"/>
+                <component-link name="tube">
+                    <parameter name="DeadTime"><value val="0.5"/></parameter>
+                </component-link>
+                <component-link name="missing-tube">
+                    <parameter name="DeadTime"><value val="0.5"/></parameter>
+                </component-link>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        assert_eq!(
+            detector_definition.unmatched_component_links,
+            ["missing-tube"]
+        );
+
+        let tube = detector_definition.component_tree.get_nodes_by_name("tube")[0];
+
+        assert_eq!(tube.get_parameter::<f64>("DeadTime").unwrap(), Some(0.5));
+    }
+
+    #[test]
+    fn test_sample_logs() {
+        use mantid_idf::{
+            parameters::{ExtractSingleValueAs, ParameterValue},
+            sample_logs::{SampleLogs, TimeSeries},
+            Point,
+        };
+
+        let content = r#"
+            <instrument name="Test">
+                <defaults><length unit="mm"/></defaults>
+                <component type="arm">
+                    <location z="1000"/>
+                    <parameter name="rotz">
+                        <logfile id="arm_angle" extract-single-value-as="position 1"/>
+                    </parameter>
+                    <parameter name="r-position">
+                        <logfile id="arm_distance" eq="value/1000"/>
+                    </parameter>
+                    <parameter name="Efixed">
+                        <formula eq="100.0+10*centre+centre^2" unit="TOF" result-unit="meV"/>
+                    </parameter>
+                </component>
+                <type name="arm">
+                    <component type="pixel"><location x="100"/></component>
+                </type>
+                <type name="pixel" is="detector"/>
+            </instrument>
+        "#;
+
+        let mut detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let arm = detector_definition.component_tree.get_nodes_by_name("arm")[0];
+
+        let Some(ParameterValue::LogFile(log_file)) =
+            arm.component.parameters["rotz"].value.as_ref()
+        else {
+            panic!("rotz should be a logfile parameter");
+        };
+
+        assert_eq!(log_file.id, "arm_angle");
+        assert_eq!(
+            log_file.extract_single_value_as,
+            ExtractSingleValueAs::Position(1)
+        );
+
+        let Some(ParameterValue::Formula(formula)) =
+            arm.component.parameters["Efixed"].value.as_ref()
+        else {
+            panic!("Efixed should be a formula parameter");
+        };
+
+        assert_eq!(formula.evaluate(2.0).unwrap(), 124.0);
+        assert_eq!(formula.result_unit.as_deref(), Some("meV"));
+
+        let sample_logs = SampleLogs::from([
+            (
+                "arm_angle".to_string(),
+                TimeSeries::from_values(vec![0.0, 90.0, 45.0]),
+            ),
+            (
+                "arm_distance".to_string(),
+                TimeSeries::from_values(vec![2000.0]),
+            ),
+        ]);
+
+        let values = arm.resolve_parameters(&sample_logs).unwrap();
+
+        assert_eq!(values["rotz"], 90.0);
+        assert_eq!(values["r-position"], 2.0);
+        assert!(!values.contains_key("Efixed"));
+
+        detector_definition.apply_sample_logs(&sample_logs).unwrap();
+
+        // The arm is moved to 2 m along the beam and rotated by 90 degrees around z
+        let arm = detector_definition.component_tree.get_nodes_by_name("arm")[0];
+        let location = &arm.component.location[0];
+        let defaults = arm.get_defaults();
+
+        assert_eq!(location.translation.len(), 1);
+        assert!(
+            (location.translation[0].to_cartesian(defaults) - Point::new(0.0, 0.0, 2.0)).norm()
+                < 1e-6
+        );
+
+        assert_eq!(location.rotation.len(), 1);
+
+        let rotation = location.rotation[0].to_rotation3(defaults);
+
+        assert!((rotation * Point::new(1.0, 0.0, 0.0) - Point::new(0.0, 1.0, 0.0)).norm() < 1e-6);
+    }
+
+    #[test]
+    fn test_sample_logs_of_locations() {
+        use mantid_idf::{
+            parameter_file::ParameterFile,
+            sample_logs::{SampleLogs, TimeSeries},
+            Point,
+        };
+
+        let content = r#"
+            <instrument name="Test">
+                <component type="tube">
+                    <locations n-elements="3" name="tube" name-count-start="1" x="0" x-end="2"/>
+                </component>
+                <type name="tube" is="detector"/>
+            </instrument>
+        "#;
+
+        let mut detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let parameter_file = ParameterFile::from_str(
+            r#"
+            <parameter-file instrument="Test">
+                <component-link name="tube2">
+                    <parameter name="y">
+                        <logfile id="tube_height"/>
+                    </parameter>
+                </component-link>
+            </parameter-file>
+            "#,
+        )
+        .expect("could not parse parameter file");
+
+        assert!(detector_definition
+            .apply_parameter_file(&parameter_file)
+            .is_empty());
+
+        // The first height is held three times as long as the second one, the last one is not held at all
+        let sample_logs = SampleLogs::from([(
+            "tube_height".to_string(),
+            TimeSeries {
+                times: vec![0.0, 3.0, 4.0],
+                values: vec![1.0, 5.0, 100.0],
+            },
+        )]);
+
+        detector_definition.apply_sample_logs(&sample_logs).unwrap();
+
+        let points = detector_definition
+            .component_tree
+            .get_special_type_points()
+            .expect("could not expand the detectors");
+
+        // Only the second tube is moved, to the time-weighted mean of the sample log
+        let expected = [
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(1.0, 2.0, 0.0),
+            Point::new(2.0, 0.0, 0.0),
+        ];
+
+        assert_eq!(points.len(), expected.len());
+
+        for (point, expected) in points.iter().zip(expected.iter()) {
+            assert!((point - expected).norm() < 1e-6);
+        }
+
+        // The elements of the locations keep their names
+        assert!(detector_definition
+            .component_tree
+            .get_nodes_by_name("tube2")[0]
+            .component
+            .locations
+            .is_empty());
+        assert_eq!(
+            detector_definition
+                .component_tree
+                .get_nodes_by_name("tube3")[0]
                 .component
-                .type_name,
-            "VoxelsRoot"
+                .location_indices_by_name("tube3"),
+            [2]
+        );
+    }
+
+    #[test]
+    fn test_lookup_table() {
+        use mantid_idf::parameters::{Interpolation, LookupTable};
+
+        let content = r#"
+            <instrument name="Test">
+                <component type="bank">
+                    <location/>
+                    <parameter name="efficiency">
+                        <lookuptable interpolation="histogram" x-unit="Wavelength" y-unit="">
+                            <point x="1.0" y="0.5"/>
+                            <point x="2.0" y="0.7"/>
+                            <point x="4.0" y="0.9"/>
+                        </lookuptable>
+                    </parameter>
+                </component>
+                <type name="bank">
+                    <component type="pixel"><location/></component>
+                </type>
+                <type name="pixel" is="detector"/>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let pixel = detector_definition
+            .component_tree
+            .get_nodes_by_name("pixel")[0];
+
+        let lookup_table = pixel
+            .get_parameter::<LookupTable>("efficiency")
+            .unwrap()
+            .expect("lookuptable should be inherited from the bank");
+
+        assert_eq!(lookup_table.interpolation, Interpolation::Histogram);
+        assert_eq!(lookup_table.x_unit.as_deref(), Some("Wavelength"));
+        assert_eq!(lookup_table.points.len(), 3);
+        assert_eq!(lookup_table.value(3.0), Some(0.7));
+        assert_eq!(lookup_table.value(0.0), Some(0.5));
+
+        // A lookuptable has no single value
+        assert!(pixel.get_parameter::<f64>("efficiency").is_err());
+    }
+
+    #[test]
+    fn test_cuboid_shapes() {
+        use mantid_idf::{shapes::Shapes, Point};
+
+        let content = r#"
+            <instrument name="Test">
+                <type name="points" is="detector">
+                    <cuboid id="points-shape">
+                        <left-front-bottom-point x="-0.5" y="-1.0" z="-2.0"/>
+                        <left-front-top-point x="-0.5" y="1.0" z="-2.0"/>
+                        <left-back-bottom-point x="-0.5" y="-1.0" z="2.0"/>
+                        <right-front-bottom-point x="0.5" y="-1.0" z="-2.0"/>
+                    </cuboid>
+                    <algebra val="points-shape"/>
+                </type>
+                <type name="dimensions" is="detector">
+                    <cuboid id="dimensions-shape">
+                        <width val="1.0"/>
+                        <height val="2.0"/>
+                        <depth val="4.0"/>
+                        <centre x="1.0" y="0.0" z="0.0"/>
+                        <axis x="1.0" y="0.0" z="0.0"/>
+                    </cuboid>
+                    <algebra val="dimensions-shape"/>
+                </type>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let Some(Shapes::Cuboid(points)) = detector_definition.types["points"].shape.as_ref()
+        else {
+            panic!("points should have a cuboid shape");
+        };
+
+        assert_eq!(points.id, "points-shape");
+        assert_eq!(points.depth, 4.0);
+        assert_eq!(points.centre, Point::origin());
+
+        let Some(Shapes::Cuboid(dimensions)) =
+            detector_definition.types["dimensions"].shape.as_ref()
+        else {
+            panic!("dimensions should have a cuboid shape");
+        };
+
+        assert_eq!(dimensions.centre, Point::new(1.0, 0.0, 0.0));
+
+        // The depth of the cuboid is rotated onto the x-axis
+        assert!((dimensions.left_front_bottom_point.x - -1.0).abs() < 1e-6);
+        assert!((dimensions.left_back_bottom_point.x - 3.0).abs() < 1e-6);
+
+        // The hexahedrons of the test detector definition are still parsed
+        let content =
+            std::fs::read_to_string(TEST_DETECTOR_DEFINITION_PATH).expect("could not read file");
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(&content)
+            .expect("could not parse detector definition");
+
+        assert!(detector_definition
+            .types
+            .values()
+            .any(|type_| matches!(type_.shape, Some(Shapes::Hexahedron(_)))));
+    }
+
+    #[test]
+    fn test_round_shapes() {
+        use mantid_idf::{shapes::Shapes, Point};
+
+        let content = r#"
+            <instrument name="Test">
+                <type name="tube-pixel" is="detector">
+                    <cylinder id="cyl-approx">
+                        <centre-of-bottom-base r="0.0" t="0.0" p="0.0"/>
+                        <axis x="0.0" y="1.0" z="0.0"/>
+                        <radius val="0.0127"/>
+                        <height val="0.002"/>
+                    </cylinder>
+                    <algebra val="cyl-approx"/>
+                </type>
+                <type name="tube-wall">
+                    <hollow-cylinder id="wall">
+                        <centre-of-bottom-base x="0.0" y="-0.5" z="0.0"/>
+                        <axis x="0.0" y="1.0" z="0.0"/>
+                        <inner-radius val="0.012"/>
+                        <outer-radius val="0.0127"/>
+                        <height val="1.0"/>
+                    </hollow-cylinder>
+                </type>
+                <type name="monitor" is="monitor">
+                    <sphere id="some-shape">
+                        <centre x="0.0" y="0.0" z="0.0"/>
+                        <radius val="0.01"/>
+                    </sphere>
+                    <algebra val="some-shape"/>
+                </type>
+                <type name="collimator">
+                    <cone id="some-cone">
+                        <tip-point x="0.0" y="0.0" z="0.0"/>
+                        <axis x="0.0" y="0.0" z="1.0"/>
+                        <angle val="45.0"/>
+                        <height val="2.0"/>
+                    </cone>
+                </type>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let types = &detector_definition.types;
+
+        let Some(Shapes::Cylinder(cylinder)) = types["tube-pixel"].shape.as_ref() else {
+            panic!("tube-pixel should have a cylinder shape");
+        };
+
+        assert_eq!(cylinder.radius, 0.0127);
+        assert_eq!(cylinder.centre_of_top_base(), Point::new(0.0, 0.002, 0.0));
+
+        let Some(Shapes::HollowCylinder(hollow_cylinder)) = types["tube-wall"].shape.as_ref()
+        else {
+            panic!("tube-wall should have a hollow-cylinder shape");
+        };
+
+        assert_eq!(hollow_cylinder.inner_radius, 0.012);
+        assert_eq!(hollow_cylinder.outer_radius, 0.0127);
+
+        let Some(Shapes::Sphere(sphere)) = types["monitor"].shape.as_ref() else {
+            panic!("monitor should have a sphere shape");
+        };
+
+        assert_eq!(sphere.radius, 0.01);
+
+        let Some(Shapes::Cone(cone)) = types["collimator"].shape.as_ref() else {
+            panic!("collimator should have a cone shape");
+        };
+
+        assert!((cone.base_radius() - 2.0).abs() < 1e-6);
+
+        // Missing elements are reported
+        let content = r#"
+            <instrument name="Test">
+                <type name="monitor" is="monitor">
+                    <sphere id="some-shape"><centre x="0.0" y="0.0" z="0.0"/></sphere>
+                </type>
+            </instrument>
+        "#;
+
+        assert!(mantid_idf::DetectorDefinition::from_str(content).is_err());
+    }
+
+    #[test]
+    fn test_shape_algebra() {
+        use mantid_idf::shapes::Shapes;
+
+        let content = r#"
+            <instrument name="Test">
+                <type name="finite-cylinder" is="detector">
+                    <infinite-cylinder id="shaft">
+                        <centre x="0.0" y="0.0" z="0.0"/>
+                        <axis x="0.0" y="0.0" z="1.0"/>
+                        <radius val="0.5"/>
+                    </infinite-cylinder>
+                    <infinite-plane id="top">
+                        <point-in-plane x="0.0" y="0.0" z="1.0"/>
+                        <normal-to-plane x="0.0" y="0.0" z="-1.0"/>
+                    </infinite-plane>
+                    <infinite-plane id="bottom">
+                        <point-in-plane x="0.0" y="0.0" z="-1.0"/>
+                        <normal-to-plane x="0.0" y="0.0" z="1.0"/>
+                    </infinite-plane>
+                    <algebra val="shaft top bottom"/>
+                </type>
+                <type name="collimator">
+                    <sphere id="a"><radius val="1.0"/></sphere>
+                    <sphere id="b"><centre x="1.0"/><radius val="1.0"/></sphere>
+                    <infinite-cone id="c">
+                        <tip-point x="0.0" y="0.0" z="0.0"/>
+                        <axis x="0.0" y="0.0" z="1.0"/>
+                        <angle val="10.0"/>
+                    </infinite-cone>
+                    <algebra val="(a : b) # c"/>
+                </type>
+                <type name="no-algebra">
+                    <sphere id="a"><radius val="1.0"/></sphere>
+                    <sphere id="b"><radius val="2.0"/></sphere>
+                </type>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let types = &detector_definition.types;
+
+        let Some(Shapes::Intersection(shapes)) = types["finite-cylinder"].shape.as_ref() else {
+            panic!("finite-cylinder should be an intersection");
+        };
+
+        assert!(matches!(shapes[0], Shapes::InfiniteCylinder(_)));
+        assert!(matches!(&shapes[1], Shapes::InfinitePlane(plane) if plane.id == "top"));
+        assert!(matches!(&shapes[2], Shapes::InfinitePlane(plane) if plane.id == "bottom"));
+
+        let Some(Shapes::Intersection(shapes)) = types["collimator"].shape.as_ref() else {
+            panic!("collimator should be an intersection");
+        };
+
+        let Shapes::Union(union) = &shapes[0] else {
+            panic!("the first part of the collimator should be a union");
+        };
+
+        assert_eq!(union.len(), 2);
+
+        let Shapes::Complement(complement) = &shapes[1] else {
+            panic!("the second part of the collimator should be a complement");
+        };
+
+        assert!(matches!(complement.as_ref(), Shapes::InfiniteCone(_)));
+
+        assert!(matches!(
+            types["no-algebra"].shape,
+            Some(Shapes::Intersection(_))
+        ));
+
+        // Unknown shapes in the algebra are reported
+        let content = r#"
+            <instrument name="Test">
+                <type name="broken">
+                    <sphere id="a"><radius val="1.0"/></sphere>
+                    <algebra val="a : b"/>
+                </type>
+            </instrument>
+        "#;
+
+        assert!(mantid_idf::DetectorDefinition::from_str(content).is_err());
+
+        // Combined components without an algebra are intersected as well
+        let content = r#"
+            <instrument name="Test">
+                <type name="pack">
+                    <combine-components-into-one-shape/>
+                    <component type="ball">
+                        <location name="left" x="-0.5"/>
+                        <location name="right" x="0.5"/>
+                    </component>
+                </type>
+                <type name="ball">
+                    <sphere id="ball-shape"><radius val="1.0"/></sphere>
+                </type>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let Some(Shapes::Intersection(shapes)) = detector_definition.types["pack"].shape.as_ref()
+        else {
+            panic!("pack should be an intersection");
+        };
+
+        assert_eq!(shapes.len(), 2);
+    }
+
+    #[test]
+    fn test_curved_shapes() {
+        use mantid_idf::shapes::{Aperture, Shapes};
+
+        let content = r#"
+            <instrument name="Test">
+                <component type="curved-bank">
+                    <location x="1" rot="90" axis-x="1" axis-y="0" axis-z="0"/>
+                </component>
+                <type name="curved-bank" is="detector">
+                    <slice-of-cylinder-ring id="bank-shape">
+                        <inner-radius val="2.5"/>
+                        <outer-radius val="2.6"/>
+                        <depth val="0.4"/>
+                        <arc val="45.0"/>
+                    </slice-of-cylinder-ring>
+                </type>
+                <type name="cryostat">
+                    <torus id="ring">
+                        <centre x="0.0" y="0.0" z="0.0"/>
+                        <axis x="0.0" y="1.0" z="0.0"/>
+                        <radius-from-centre-to-tube val="0.3"/>
+                        <radius-tube val="0.05"/>
+                    </torus>
+                </type>
+                <type name="guide">
+                    <tapered-guide id="guide-shape">
+                        <aperture-start height="0.1" width="0.05"/>
+                        <length val="3.0"/>
+                        <aperture-end height="0.06" width="0.03"/>
+                        <centre x="0.0" y="0.0" z="-5.0"/>
+                    </tapered-guide>
+                </type>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let types = &detector_definition.types;
+
+        let Some(Shapes::SliceOfCylinderRing(slice)) = types["curved-bank"].shape.as_ref() else {
+            panic!("curved-bank should have a slice-of-cylinder-ring shape");
+        };
+
+        assert_eq!(
+            (
+                slice.inner_radius,
+                slice.outer_radius,
+                slice.depth,
+                slice.arc
+            ),
+            (2.5, 2.6, 0.4, 45.0)
+        );
+
+        let Some(Shapes::Torus(torus)) = types["cryostat"].shape.as_ref() else {
+            panic!("cryostat should have a torus shape");
+        };
+
+        assert_eq!(torus.radius_from_centre_to_tube, 0.3);
+        assert_eq!(torus.radius_tube, 0.05);
+
+        let Some(Shapes::TaperedGuide(guide)) = types["guide"].shape.as_ref() else {
+            panic!("guide should have a tapered-guide shape");
+        };
+
+        assert_eq!(
+            guide.aperture_start,
+            Aperture {
+                width: 0.05,
+                height: 0.1
+            }
+        );
+        assert_eq!(guide.aperture_end.width, 0.03);
+        assert_eq!(guide.length, 3.0);
+        assert_eq!(guide.centre.z, -5.0);
+        assert_eq!(guide.axis, nalgebra::Vector3::z());
+        assert_eq!(guide.width_axis, nalgebra::Vector3::x());
+
+        // The apertures turn with the guide
+        let turned = types["guide"]
+            .shape
+            .as_ref()
+            .unwrap()
+            .transformed(&nalgebra::Isometry3::rotation(
+                nalgebra::Vector3::z() * std::f32::consts::FRAC_PI_2,
+            ))
+            .unwrap();
+
+        let Shapes::TaperedGuide(turned) = turned else {
+            panic!("guide should stay a tapered guide");
+        };
+
+        assert!((turned.width_axis - nalgebra::Vector3::y()).norm() < 1e-6);
+
+        // The placed curved bank is moved and its axis rotated from z to -y
+        let detectors = detector_definition
+            .component_tree
+            .get_detectors()
+            .expect("could not expand the detectors");
+
+        let Some(Shapes::SliceOfCylinderRing(placed)) = detectors[0]
+            .shape(types)
+            .expect("could not move the shape of the curved bank")
+        else {
+            panic!("the placed curved bank should have a slice-of-cylinder-ring shape");
+        };
+
+        assert!((placed.centre() - mantid_idf::Point::new(1.0, 0.0, 0.0)).norm() < 1e-6);
+        assert!((placed.axis() + nalgebra::Vector3::y()).norm() < 1e-6);
+        assert_eq!(placed.outer_radius, 2.6);
+    }
+
+    #[test]
+    fn test_combined_shapes() {
+        use mantid_idf::{
+            shapes::{BoundingBox, Shapes},
+            Point,
+        };
+
+        let content = r#"
+            <instrument name="Test">
+                <component type="pack"><location x="1"/></component>
+                <type name="pack" is="detector">
+                    <combine-components-into-one-shape/>
+                    <component type="tube">
+                        <location name="left" x="-0.01"/>
+                        <location name="right" x="0.01"/>
+                    </component>
+                    <component type="cap">
+                        <location name="cap" y="0.5"/>
+                    </component>
+                    <algebra val="(left : right) : cap"/>
+                    <translate-rotate-combined-shape-to y="0.5"/>
+                    <bounding-box>
+                        <x-min val="-0.02"/>
+                        <x-max val="0.02"/>
+                        <y-min val="-0.5"/>
+                        <y-max val="0.1"/>
+                        <z-min val="-0.01"/>
+                        <z-max val="0.01"/>
+                    </bounding-box>
+                </type>
+                <type name="tube">
+                    <cylinder id="tube-shape">
+                        <centre-of-bottom-base x="0.0" y="0.0" z="0.0"/>
+                        <axis x="0.0" y="1.0" z="0.0"/>
+                        <radius val="0.01"/>
+                        <height val="0.5"/>
+                    </cylinder>
+                </type>
+                <type name="cap">
+                    <sphere id="cap-shape"><radius val="0.02"/></sphere>
+                </type>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let pack = &detector_definition.types["pack"];
+
+        assert!(pack.combine_components_into_one_shape);
+        assert!(pack.components.is_empty());
+        assert_eq!(
+            pack.bounding_box,
+            Some(BoundingBox {
+                x_min: -0.02,
+                x_max: 0.02,
+                y_min: -0.5,
+                y_max: 0.1,
+                z_min: -0.01,
+                z_max: 0.01,
+            })
         );
+
+        let Some(Shapes::Union(shapes)) = pack.shape.as_ref() else {
+            panic!("pack should be a union of shapes");
+        };
+
+        let Shapes::Union(tubes) = &shapes[0] else {
+            panic!("the tubes should be a union");
+        };
+
+        // The shapes are moved to the locations of the components and then by the inverse of the
+        // `translate-rotate-combined-shape-to` location
+        let Shapes::Cylinder(left) = &tubes[0] else {
+            panic!("left should be a cylinder");
+        };
+
+        assert!((left.centre_of_bottom_base - Point::new(-0.01, -0.5, 0.0)).norm() < 1e-6);
+
+        let Shapes::Sphere(cap) = &shapes[1] else {
+            panic!("cap should be a sphere");
+        };
+
+        assert!((cap.centre - Point::origin()).norm() < 1e-6);
+
+        // The combined type is a leaf of the component tree
+        let pack_node = detector_definition.component_tree.get_nodes_by_name("pack")[0];
+
+        assert!(pack_node.children.is_empty());
+
+        // The bounding box is moved with the detector
+        let detectors = detector_definition
+            .component_tree
+            .get_detectors()
+            .expect("could not expand the detectors");
+
+        let bounding_box = detectors[0]
+            .bounding_box(&detector_definition.types)
+            .unwrap()
+            .expect("pack has a bounding box");
+
+        assert!((bounding_box.x_min - 0.98).abs() < 1e-6);
+        assert!((bounding_box.x_max - 1.02).abs() < 1e-6);
+        assert!((bounding_box.y_max - 0.1).abs() < 1e-6);
+
+        // A type that contains itself can not be combined
+        let content = r#"
+            <instrument name="Test">
+                <component type="outer"><location/></component>
+                <type name="outer" is="detector">
+                    <combine-components-into-one-shape/>
+                    <component type="inner"><location name="inner"/></component>
+                </type>
+                <type name="inner">
+                    <combine-components-into-one-shape/>
+                    <component type="outer"><location name="outer"/></component>
+                </type>
+            </instrument>
+        "#;
+
+        let error = mantid_idf::DetectorDefinition::from_str(content)
+            .expect_err("a cycle of combined types is an error");
+
+        assert!(format!("{:#}", error).contains("contains itself"));
+    }
+
+    #[test]
+    fn test_nested_transforms() {
+        use mantid_idf::{structs::Transform, Point};
+
+        let content = r#"
+            <instrument name="Test">
+                <component type="bank">
+                    <location z="2">
+                        <rot val="90">
+                            <trans x="1"/>
+                        </rot>
+                    </location>
+                </component>
+                <type name="bank">
+                    <component type="pixel">
+                        <location/>
+                        <location y="1"/>
+                    </component>
+                </type>
+                <type name="pixel" is="detector"/>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let bank = detector_definition.component_tree.get_nodes_by_name("bank")[0];
+        let location = &bank.component.location[0];
+
+        assert!(location.rotation.is_empty());
+        assert_eq!(location.nested.len(), 1);
+        assert!(matches!(
+            location.nested[0].transform,
+            Transform::Rotation(_)
+        ));
+        assert_eq!(location.nested[0].children.len(), 1);
+        assert!(matches!(
+            location.nested[0].children[0].transform,
+            Transform::Translation(_)
+        ));
+
+        // The translation is applied in the frame rotated by the outer `rot` tag
+        let points = detector_definition
+            .component_tree
+            .get_special_type_points()
+            .expect("could not expand the detectors");
+
+        assert_eq!(points.len(), 2);
+        assert!((points[0] - Point::new(0.0, 1.0, 2.0)).norm() < 1e-6);
+        assert!((points[1] - Point::new(-1.0, 1.0, 2.0)).norm() < 1e-6);
+
+        // The nested `rot` tags of the test instrument: <rot val="-90" axis-x="1"><rot val="-90"/></rot>
+        let content =
+            std::fs::read_to_string(TEST_DETECTOR_DEFINITION_PATH).expect("could not read file");
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(&content)
+            .expect("could not parse detector definition");
+
+        let voxels = detector_definition
+            .component_tree
+            .get_nodes_by_name("VoxelsRoot/Voxels")[0];
+        let isometry = voxels.component.location[0].to_isometry(voxels.get_defaults());
+
+        // Rx(-90) * Rz(-90) maps x to -y and then to z
+        assert!((isometry * Point::new(1.0, 0.0, 0.0) - Point::new(0.0, 0.0, 1.0)).norm() < 1e-6);
+    }
+
+    #[test]
+    fn test_units_and_spherical_locations() {
+        use mantid_idf::Point;
+        use std::f32::consts::FRAC_1_SQRT_2;
+
+        let content = r#"
+            <instrument name="Test">
+                <defaults>
+                    <length unit="mm"/>
+                    <angle unit="radian"/>
+                </defaults>
+                <component type="bank">
+                    <location z="1000" rot="3.1415927"/>
+                </component>
+                <type name="bank">
+                    <component type="pixel">
+                        <locations n-elements="3" r="2000" t="0" p="0" r-end="2000" t-end="1.5707964" p-end="0"/>
+                    </component>
+                </type>
+                <type name="pixel" is="detector"/>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let points = detector_definition
+            .component_tree
+            .get_special_type_points()
+            .expect("could not expand the detectors");
+
+        // The pixels are interpolated in spherical coordinates and the bank is turned around z by pi
+        let expected = [
+            Point::new(0.0, 0.0, 3.0),
+            Point::new(-2.0 * FRAC_1_SQRT_2, 0.0, 1.0 + 2.0 * FRAC_1_SQRT_2),
+            Point::new(-2.0, 0.0, 1.0),
+        ];
+
+        assert_eq!(points.len(), expected.len());
+
+        for (point, expected) in points.iter().zip(expected.iter()) {
+            assert!((point - expected).norm() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_facing() {
+        use nalgebra::Vector3;
+
+        let content = r#"
+            <instrument name="Test">
+                <defaults><components-are-facing x="0.0" y="0.0" z="0.0"/></defaults>
+                <component type="pixel">
+                    <location x="2"/>
+                    <location y="2"><facing val="none"/></location>
+                    <location z="2"><facing x="0" y="0" z="4"></facing></location>
+                    <location x="-2"><facing r="0" t="0" p="0" rot="90"/></location>
+                </component>
+                <type name="pixel" is="detector"/>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let pixel = detector_definition
+            .component_tree
+            .get_nodes_by_name("pixel")[0];
+
+        assert!(pixel.component.location[0].facing.is_none());
+        assert!(pixel.component.location[1]
+            .facing
+            .as_ref()
+            .is_some_and(|facing| facing.point.is_none()));
+        assert_eq!(
+            pixel.component.location[3]
+                .facing
+                .as_ref()
+                .and_then(|facing| facing.rot),
+            Some(90.0)
+        );
+
+        let transformations = detector_definition
+            .component_tree
+            .get_special_type_transformations()
+            .expect("could not expand the detectors");
+
+        // The z-axis of a pixel points from the facing point towards the pixel
+        let normals = transformations
+            .iter()
+            .map(|transformation| transformation * Vector3::z())
+            .collect::<Vec<_>>();
+
+        assert!((normals[0] - Vector3::x()).norm() < 1e-6);
+        assert!((normals[1] - Vector3::z()).norm() < 1e-6);
+        assert!((normals[2] + Vector3::z()).norm() < 1e-6);
+        assert!((normals[3] + Vector3::x()).norm() < 1e-6);
+
+        // `rot` turns the pixel around its own z-axis before it is rotated to face the point
+        assert!((transformations[3] * Vector3::x() - Vector3::y()).norm() < 1e-6);
+    }
+
+    #[test]
+    fn test_exclude_sub_parts() {
+        use mantid_idf::Point;
+
+        let content = r#"
+            <instrument name="Test">
+                <component type="bank">
+                    <location name="bank_a"><exclude sub-part="tube2"/></location>
+                    <location name="bank_b" x="10"/>
+                </component>
+                <component type="blocked-bank">
+                    <location>
+                        <exclude sub-part="cover"></exclude>
+                    </location>
+                </component>
+                <type name="bank">
+                    <component type="tube">
+                        <locations name="tube" name-count-start="1" n-elements="3" x="1" x-end="3"/>
+                    </component>
+                </type>
+                <type name="blocked-bank">
+                    <component type="tube"><location name="cover"/></component>
+                </type>
+                <type name="tube" is="detector"/>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let tree = &detector_definition.component_tree;
+
+        assert_eq!(
+            tree.get_nodes_by_name("bank_a")[0].component.location[0].exclude,
+            ["tube2"]
+        );
+
+        // The cover is left out at the only location of the blocked bank
+        assert!(tree.get_nodes_by_name("blocked-bank")[0]
+            .children
+            .is_empty());
+
+        let points = tree
+            .get_special_type_points()
+            .expect("could not expand the detectors");
+
+        let expected = [
+            Point::new(1.0, 0.0, 0.0),
+            Point::new(3.0, 0.0, 0.0),
+            Point::new(11.0, 0.0, 0.0),
+            Point::new(12.0, 0.0, 0.0),
+            Point::new(13.0, 0.0, 0.0),
+        ];
+
+        assert_eq!(points.len(), expected.len());
+
+        for (point, expected) in points.iter().zip(expected.iter()) {
+            assert!((point - expected).norm() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_neutronic_positions() {
+        use mantid_idf::{component_tree::Geometry, Point};
+
+        let content = r#"
+            <instrument name="Test">
+                <component type="bank">
+                    <location z="1"/>
+                </component>
+                <type name="bank">
+                    <component type="pixel">
+                        <location x="1">
+                            <neutronic x="2">
+                                <rot val="90">
+                                    <trans x="1"/>
+                                </rot>
+                            </neutronic>
+                        </location>
+                        <location x="-1"/>
+                    </component>
+                </type>
+                <type name="pixel" is="detector"/>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let tree = &detector_definition.component_tree;
+
+        let location = &tree.get_nodes_by_name("pixel")[0].component.location[0];
+
+        assert!(location.nested.is_empty());
+        assert_eq!(location.neutronic.as_ref().unwrap().nested.len(), 1);
+
+        let physical = tree
+            .get_special_type_points_in(Geometry::Physical)
+            .expect("could not expand the detectors");
+        let neutronic = tree
+            .get_special_type_points_in(Geometry::Neutronic)
+            .expect("could not expand the detectors");
+
+        assert!((physical[0] - Point::new(1.0, 0.0, 1.0)).norm() < 1e-6);
+        assert!((physical[1] - Point::new(-1.0, 0.0, 1.0)).norm() < 1e-6);
+
+        // The neutronic location is relative to the parent as well,
+        // locations without a neutronic position keep their physical one
+        assert!((neutronic[0] - Point::new(2.0, 1.0, 1.0)).norm() < 1e-6);
+        assert!((neutronic[1] - Point::new(-1.0, 0.0, 1.0)).norm() < 1e-6);
+    }
+
+    #[test]
+    fn test_delta_spherical_offsets() {
+        use mantid_idf::{defaults::SphericalOffsets, Point};
+
+        let content = r#"
+            <instrument name="Test">
+                <defaults><offsets spherical="delta"/></defaults>
+                <component type="bank">
+                    <location r="2" t="90" p="0"/>
+                </component>
+                <component type="direction">
+                    <location t="90" p="90"/>
+                </component>
+                <type name="bank">
+                    <component type="pixel">
+                        <location t="10"/>
+                        <location r="1"/>
+                        <location x="0.5"/>
+                    </component>
+                </type>
+                <type name="direction">
+                    <component type="pixel"><location r="1"/></component>
+                </type>
+                <type name="pixel" is="detector"/>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        assert_eq!(
+            detector_definition.defaults.offsets.spherical,
+            SphericalOffsets::Delta
+        );
+
+        let points = detector_definition
+            .component_tree
+            .get_special_type_points()
+            .expect("could not expand the detectors");
+
+        let theta = 100.0_f32.to_radians();
+
+        let expected = [
+            // The angles and the radius are added to the ones of the bank
+            Point::new(2.0 * theta.sin(), 0.0, 2.0 * theta.cos()),
+            Point::new(3.0, 0.0, 0.0),
+            // Cartesian translations stay relative to the parent
+            Point::new(2.5, 0.0, 0.0),
+            // The direction of a parent at the origin is kept
+            Point::new(0.0, 1.0, 0.0),
+        ];
+
+        assert_eq!(points.len(), expected.len());
+
+        for (point, expected) in points.iter().zip(expected.iter()) {
+            assert!((point - expected).norm() < 1e-6, "{point} != {expected}");
+        }
+    }
+
+    #[test]
+    fn test_side_by_side_view_locations() {
+        use nalgebra::Point2;
+
+        let content = r#"
+            <instrument name="Test">
+                <component type="detectors"><location/></component>
+                <type name="detectors">
+                    <component type="bank1">
+                        <location x="-1"/>
+                        <side-by-side-view-location x="-0.5" y="0.2"/>
+                    </component>
+                    <component type="bank2">
+                        <location x="1"/>
+                        <side-by-side-view-location x="0.5"></side-by-side-view-location>
+                    </component>
+                </type>
+                <type name="bank1" is="detector"/>
+                <type name="bank2" is="detector"/>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let locations = detector_definition
+            .component_tree
+            .get_side_by_side_view_locations()
+            .into_iter()
+            .map(|(node, location)| (node.component.type_name.as_str(), location))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            locations,
+            [
+                ("bank1", Point2::new(-0.5, 0.2)),
+                ("bank2", Point2::new(0.5, 0.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_instrument_metadata() {
+        use chrono::NaiveDate;
+
+        let content =
+            std::fs::read_to_string(TEST_DETECTOR_DEFINITION_PATH).expect("could not read file");
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(&content)
+            .expect("could not parse detector definition");
+
+        let instrument = &detector_definition.instrument;
+
+        assert_eq!(instrument.name, "Test");
+        assert_eq!(
+            instrument.valid_from,
+            NaiveDate::from_ymd_opt(2024, 10, 18).and_then(|date| date.and_hms_opt(0, 0, 1))
+        );
+        assert_eq!(
+            instrument.valid_to,
+            NaiveDate::from_ymd_opt(2100, 1, 31).and_then(|date| date.and_hms_opt(23, 59, 59))
+        );
+        assert_eq!(
+            instrument.last_modified,
+            NaiveDate::from_ymd_opt(2024, 10, 18)
+                .and_then(|date| date.and_hms_micro_opt(12, 1, 44, 111_110))
+        );
+        assert!(instrument
+            .schema_location
+            .as_deref()
+            .is_some_and(|schema_location| schema_location.ends_with("IDFSchema.xsd")));
+
+        let before = NaiveDate::from_ymd_opt(2024, 10, 17)
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .unwrap();
+
+        assert!(!instrument.is_valid_at(before));
+
+        // Timestamps in ISO 8601 form are accepted as well, invalid ones are an error
+        let content = r#"<instrument name="Other" valid-from="2000-01-01T12:00:00"></instrument>"#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        assert!(detector_definition.instrument.valid_to.is_none());
+        assert!(detector_definition.instrument.is_valid_at(before));
+
+        let content = r#"<instrument name="Other" valid-from="yesterday"></instrument>"#;
+
+        assert!(mantid_idf::DetectorDefinition::from_str(content).is_err());
+
+        // Without an instrument tag the instrument has no name and no period of validity
+        let content = r#"
+            <component type="pixel"><location/></component>
+            <type name="pixel" is="detector"/>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        assert_eq!(
+            detector_definition.instrument,
+            mantid_idf::structs::Instrument::default()
+        );
+        assert!(detector_definition.instrument.is_valid_at(before));
+        assert_eq!(detector_definition.component_tree.children.len(), 1);
+    }
+
+    #[test]
+    fn test_idlist_ids() {
+        use mantid_idf::{types::SpecialTypes, Point};
+
+        let content = r#"
+            <instrument name="Test">
+                <component type="bank" idlist="bank-ids">
+                    <location x="-1"/>
+                    <location x="1"/>
+                </component>
+                <component type="monitor" idlist="monitor-ids">
+                    <location z="-2"/>
+                </component>
+                <type name="bank">
+                    <component type="pixel">
+                        <locations y="0" y-end="0.2" n-elements="3"/>
+                    </component>
+                </type>
+                <type name="pixel" is="detector"/>
+                <type name="monitor" is="monitor"/>
+                <idlist idname="bank-ids">
+                    <id start="1" end="3"/>
+                    <id start="101" end="103"/>
+                </idlist>
+                <idlist idname="monitor-ids">
+                    <id start="9" end="9"/>
+                </idlist>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let detectors = detector_definition
+            .component_tree
+            .get_detectors()
+            .expect("could not assign ids");
+
+        assert_eq!(
+            detectors
+                .iter()
+                .map(|detector| detector.id)
+                .collect::<Vec<_>>(),
+            [1, 2, 3, 101, 102, 103, 9].map(Some)
+        );
+
+        assert_eq!(detectors[3].position(), Point::new(1.0, 0.0, 0.0));
+        assert_eq!(detectors[6].special_type, SpecialTypes::Monitor);
+
+        // The number of ids has to match the number of detectors
+        let content = content.replace(
+            r#"<id start="101" end="103"/>"#,
+            r#"<id start="101" end="102"/>"#,
+        );
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(&content)
+            .expect("could not parse detector definition");
+
+        assert!(detector_definition.component_tree.get_detectors().is_err());
+
+        // Without assigning the ids the detectors can still be expanded
+        assert_eq!(
+            detector_definition
+                .component_tree
+                .get_special_type_points()
+                .expect("could not expand the detectors")
+                .len(),
+            7
+        );
+    }
+
+    #[test]
+    fn test_idlist_entries() {
+        let content = r#"
+            <instrument name="Test">
+                <component type="pixel" idlist="pixel-ids">
+                    <locations x="0" x-end="1" n-elements="4"/>
+                </component>
+                <component type="pixel" idlist="missing">
+                    <location z="1"/>
+                </component>
+                <type name="pixel" is="detector"/>
+                <idlist idname="pixel-ids">
+                    <id start="10" end="14" step="2"/>
+                    <id val="5"/>
+                </idlist>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let id_list = &detector_definition.id_lists["pixel-ids"];
+
+        assert_eq!(id_list.len(), 4);
+        assert_eq!(id_list.get_ids(), [10, 12, 14, 5]);
+        assert!(id_list.contains(12));
+        assert!(!id_list.contains(11));
+        assert!(!id_list.contains(0));
+
+        // The ids of the known idlist are assigned, the missing one is reported
+        let mismatches = detector_definition
+            .component_tree
+            .validate_id_lists()
+            .expect("could not expand the detectors");
+
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].idlist, "missing");
+        assert_eq!(mismatches[0].ids, None);
+        assert_eq!(mismatches[0].detectors, 1);
+
+        assert!(detector_definition.component_tree.get_detectors().is_err());
+
+        // Invalid `id` tags are an error
+        for id in [
+            r#"<id start="10" end="14" step="0"/>"#,
+            r#"<id start="10" end="14" step="-2"/>"#,
+            r#"<id start="14" end="10"/>"#,
+            r#"<id start="10"/>"#,
+            r#"<id start="10" end="14" val="5"/>"#,
+        ] {
+            let content = content.replace(r#"<id start="10" end="14" step="2"/>"#, id);
+
+            assert!(mantid_idf::DetectorDefinition::from_str(&content).is_err());
+        }
+    }
+
+    #[test]
+    fn test_idlist_steps() {
+        let content = r#"
+            <instrument name="Test">
+                <component type="pixel" idlist="pixel-ids">
+                    <locations x="0" x-end="49" n-elements="50"/>
+                </component>
+                <component type="monitor" idlist="monitor-ids">
+                    <locations z="-1" z-end="-3" n-elements="3"/>
+                </component>
+                <type name="pixel" is="detector"/>
+                <type name="monitor" is="monitor"/>
+                <idlist idname="pixel-ids">
+                    <id start="1" end="100" step="2"/>
+                </idlist>
+                <idlist idname="monitor-ids">
+                    <id start="-1" end="-8" step="-3"/>
+                </idlist>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        // The ids end at the last one that does not pass `end`
+        let id_list = &detector_definition.id_lists["pixel-ids"];
+
+        assert_eq!(id_list.len(), 50);
+        assert_eq!(id_list.get_ids(), (1..=99).step_by(2).collect::<Vec<_>>());
+        assert!(id_list.contains(99));
+        assert!(!id_list.contains(100));
+
+        // Negative steps count down, ids can be negative
+        let id_list = &detector_definition.id_lists["monitor-ids"];
+
+        assert_eq!(id_list.get_ids(), [-1, -4, -7]);
+        assert!(id_list.contains(-4));
+        assert!(!id_list.contains(-8));
+
+        let detectors = detector_definition
+            .component_tree
+            .get_detectors()
+            .expect("could not assign ids");
+
+        assert_eq!(detectors.len(), 53);
+        assert_eq!(detectors[49].id, Some(99));
+        assert_eq!(detectors[52].id, Some(-7));
+    }
+
+    #[test]
+    fn test_rectangular_detector() {
+        use mantid_idf::{shapes::Shapes, types::SpecialTypes, Point};
+
+        let content = r#"
+            <instrument name="Test">
+                <component type="panel" idstart="1000" idfillbyfirst="x" idstepbyrow="10">
+                    <location z="2"/>
+                </component>
+                <type name="panel" is="RectangularDetector" type="pixel"
+                    xpixels="3" xstart="-0.1" xstep="0.1"
+                    ypixels="2" ystart="0.0" ystep="0.2"/>
+                <type name="pixel" is="detector">
+                    <cuboid id="pixel-shape">
+                        <left-front-bottom-point x="-0.05" y="-0.05" z="0.0"/>
+                        <left-front-top-point x="-0.05" y="0.05" z="0.0"/>
+                        <left-back-bottom-point x="-0.05" y="-0.05" z="0.01"/>
+                        <right-front-bottom-point x="0.05" y="-0.05" z="0.0"/>
+                    </cuboid>
+                </type>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let detectors = detector_definition
+            .component_tree
+            .get_detectors()
+            .expect("could not expand the detectors");
+
+        // x is the outer index, the ids are filled along x first
+        assert_eq!(
+            detectors
+                .iter()
+                .map(|detector| detector.id.unwrap())
+                .collect::<Vec<_>>(),
+            [1000, 1010, 1001, 1011, 1002, 1012]
+        );
+        assert!((detectors[3].position() - Point::new(0.0, 0.2, 2.0)).norm() < 1e-6);
+        assert!(detectors
+            .iter()
+            .all(|detector| detector.special_type == SpecialTypes::RectangularDetector));
+
+        let Some(Shapes::Cuboid(cuboid)) = detectors[3]
+            .shape(&detector_definition.types)
+            .expect("could not transform the pixel shape")
+        else {
+            panic!("pixel should have a cuboid shape");
+        };
+
+        assert!((cuboid.left_front_bottom_point - Point::new(-0.05, 0.15, 2.0)).norm() < 1e-6);
+
+        // Filling along y is the default, with one row per column of y pixels
+        let content_y = content.replace(r#" idfillbyfirst="x" idstepbyrow="10""#, "");
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(&content_y)
+            .expect("could not parse detector definition");
+
+        let ids = detector_definition
+            .component_tree
+            .get_detectors()
+            .expect("could not expand the detectors")
+            .iter()
+            .map(|detector| detector.id.unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(ids, [1000, 1001, 1002, 1003, 1004, 1005]);
+
+        // Missing attributes are an error instead of a panic
+        let content_missing = content.replace(r#"xstep="0.1""#, "");
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(&content_missing)
+            .expect("could not parse detector definition");
+
+        assert!(detector_definition
+            .component_tree
+            .get_special_type_points()
+            .is_err());
+    }
+
+    #[test]
+    fn test_structured_detector() {
+        use mantid_idf::{types::SpecialTypes, Point};
+
+        let content = r#"
+            <instrument name="Test">
+                <component type="fan" idstart="10" idfillbyfirst="x">
+                    <location z="1"/>
+                </component>
+                <type name="fan" is="StructuredDetector" xpixels="2" ypixels="1" type="pixel">
+                    <vertex x="-1.0" y="0.0"/>
+                    <vertex x="0.0" y="0.0"/>
+                    <vertex x="1.0" y="0.0"/>
+                    <vertex x="-2.0" y="1.0"/>
+                    <vertex x="0.0" y="1.0"/>
+                    <vertex x="2.0" y="1.0"/>
+                </type>
+                <type name="pixel" is="detector"/>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        assert_eq!(detector_definition.types["fan"].vertices.len(), 6);
+
+        let detectors = detector_definition
+            .component_tree
+            .get_detectors()
+            .expect("could not expand the detectors");
+
+        assert_eq!(detectors.len(), 2);
+        assert_eq!(
+            detectors
+                .iter()
+                .map(|detector| detector.id)
+                .collect::<Vec<_>>(),
+            [Some(10), Some(11)]
+        );
+        assert!(detectors
+            .iter()
+            .all(|detector| detector.special_type == SpecialTypes::StructuredDetector));
+
+        assert!((detectors[0].position() - Point::new(-0.75, 0.5, 1.0)).norm() < 1e-6);
+        assert_eq!(
+            detectors[1].corners,
+            Some([
+                Point::new(0.0, 0.0, 1.0),
+                Point::new(1.0, 0.0, 1.0),
+                Point::new(2.0, 1.0, 1.0),
+                Point::new(0.0, 1.0, 1.0),
+            ])
+        );
+
+        // The number of vertices has to match the number of pixels
+        let content = content.replace(r#"<vertex x="2.0" y="1.0"/>"#, "");
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(&content)
+            .expect("could not parse detector definition");
+
+        assert!(detector_definition.component_tree.get_detectors().is_err());
+    }
+
+    #[test]
+    fn test_grid_detector() {
+        use mantid_idf::{shapes::Shapes, types::SpecialTypes, Point};
+
+        let content = r#"
+            <instrument name="Test">
+                <component type="voxels" idstart="100" idfillorder="zyx">
+                    <location x="1"/>
+                </component>
+                <type name="voxels" is="GridDetector" type="voxel"
+                    xpixels="2" xstart="0.0" xstep="0.1"
+                    ypixels="3" ystart="0.0" ystep="0.1"
+                    zpixels="2" zstart="0.0" zstep="0.1"/>
+                <type name="voxel" is="detector">
+                    <sphere id="voxel-shape">
+                        <centre x="0.0" y="0.0" z="0.0"/>
+                        <radius val="0.05"/>
+                    </sphere>
+                </type>
+            </instrument>
+        "#;
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(content)
+            .expect("could not parse detector definition");
+
+        let detectors = detector_definition
+            .component_tree
+            .get_detectors()
+            .expect("could not expand the detectors");
+
+        assert_eq!(detectors.len(), 12);
+        assert!(detectors
+            .iter()
+            .all(|detector| detector.special_type == SpecialTypes::GridDetector));
+
+        // x is the outermost and z the innermost index, z is filled first
+        let ids = detectors
+            .iter()
+            .map(|detector| detector.id.unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(ids, (100..112).collect::<Vec<_>>());
+
+        let last = detectors.last().unwrap();
+
+        assert!((last.position() - Point::new(1.1, 0.2, 0.1)).norm() < 1e-6);
+
+        let Some(Shapes::Sphere(sphere)) = last
+            .shape(&detector_definition.types)
+            .expect("could not transform the voxel shape")
+        else {
+            panic!("voxel should have a sphere shape");
+        };
+
+        assert!((sphere.centre - Point::new(1.1, 0.2, 0.1)).norm() < 1e-6);
+
+        // With the default fill order x is filled first
+        let content_xyz = content.replace(r#" idfillorder="zyx""#, "");
+
+        let detector_definition = mantid_idf::DetectorDefinition::from_str(&content_xyz)
+            .expect("could not parse detector definition");
+
+        let ids = detector_definition
+            .component_tree
+            .get_detectors()
+            .expect("could not expand the detectors")
+            .iter()
+            .map(|detector| detector.id.unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(ids[..4], [100, 106, 102, 108]);
+
+        // Invalid fill orders are an error
+        for idfillorder in ["xx", "xyy", "xyw"] {
+            let content = content.replace("zyx", idfillorder);
+
+            let detector_definition = mantid_idf::DetectorDefinition::from_str(&content)
+                .expect("could not parse detector definition");
+
+            assert!(detector_definition.component_tree.get_detectors().is_err());
+        }
     }
 }